@@ -1,5 +1,7 @@
 use std::env;
-use std::fs::File;
+use std::io;
+use std::io::IsTerminal;
+use std::process;
 use std::str;
 
 #[macro_use]
@@ -8,26 +10,171 @@ extern crate lazy_static;
 #[macro_use]
 mod simplelang;
 use simplelang::*;
+use simplelang::source_map::*;
+use simplelang::diagnostics::*;
 
 #[cfg(test)]
 mod tests;
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum MessageFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Command {
+    /// Prints the tokens and the syntax tree of every file.
+    Dump,
+    /// `simple_lang run file`, runs the `main` function of every file and
+    /// prints its result. `--overflow=checked|wrapping|saturating` selects
+    /// how integer overflow is handled, it is an error by default. With
+    /// `--message-format=json` the results go to stderr, so stdout only
+    /// contains JSON diagnostics.
+    Run,
+    /// `simple_lang doc file`, prints the doc comments of every file and of
+    /// the functions in it as Markdown. It cannot be combined with
+    /// `--message-format=json`, which keeps stdout for JSON diagnostics.
+    Doc,
+}
+
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    colors: bool,
+    message_format: MessageFormat,
+    overflow: interpreter::Overflow,
+    filenames: Vec<String>,
+}
+
+/// Parses the command line arguments following the program name, an error
+/// describes the invalid usage.
+fn parse_args(args: &[String]) -> Result<Options, String> {
+
+    let (command, options) = match args.first().map(|arg| &arg[..]) {
+        Some("run") => (Command::Run, &args[1..]),
+        Some("doc") => (Command::Doc, &args[1..]),
+        _ => (Command::Dump, args),
+    };
+
+    let mut colors = io::stderr().is_terminal();
+    let mut message_format = MessageFormat::Human;
+    let mut overflow = interpreter::Overflow::default();
+    let mut filenames = Vec::new();
+
+    for arg in options {
+        match &arg[..] {
+            "--message-format=human" => message_format = MessageFormat::Human,
+            "--message-format=json" => message_format = MessageFormat::Json,
+            "--color=always" => colors = true,
+            "--color=never" => colors = false,
+            "--color=auto" => colors = io::stderr().is_terminal(),
+            "--overflow=checked" => overflow = interpreter::Overflow::Checked,
+            "--overflow=wrapping" => overflow = interpreter::Overflow::Wrapping,
+            "--overflow=saturating" => overflow = interpreter::Overflow::Saturating,
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+            _ => filenames.push(arg.clone()),
+        }
+    }
+
+    if command == Command::Doc && message_format == MessageFormat::Json {
+        let message = "'doc' prints Markdown and cannot be used with '--message-format=json'";
+        return Err(message.to_string());
+    }
+
+    Ok(Options {
+        command,
+        colors,
+        message_format,
+        overflow,
+        filenames,
+    })
+}
+
 fn main() {
     let args: Vec<String> = env::args().skip(1).collect();
-    println!("Arguments {:?}", args);
 
-    let filename = &args[0];
-    let file = File::open(filename).expect("file not found");
+    let Options { command, colors, message_format, overflow, filenames } = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
 
-    let tokens = lexer::lex(file).expect("Error while lexing source");
+    let verbose = command == Command::Dump && message_format == MessageFormat::Human;
+    if verbose {
+        println!("Arguments {:?}", args);
+    }
 
-    for token in &tokens {
-        dump!(token);
+    let mut source_map = SourceMap::new();
+    for filename in filenames {
+        source_map.load_file(&filename).expect("file not found");
     }
 
-    let expression = parser::expression(tokens.as_slice())
-        .expect("Error while parsing source")
-        .1;
+    let renderer = Renderer::new(colors);
+    let mut has_errors = false;
+
+    let emit = |diagnostic: &Diagnostic| match message_format {
+        MessageFormat::Human => eprint!("{}", renderer.render(&source_map, diagnostic)),
+        MessageFormat::Json => println!("{}", to_json(&source_map, diagnostic)),
+    };
+
+    for file in source_map.files() {
+        let (module, diagnostics) = compile_file(file, verbose);
+        for diagnostic in &diagnostics {
+            emit(diagnostic);
+        }
+        let file_has_errors = diagnostics.iter().any(|d| d.severity == Severity::Error);
+        has_errors |= file_has_errors;
+
+        if command == Command::Doc {
+            print!("{}", docs::render_markdown(file.name(), &module));
+        }
+
+        if command == Command::Run && !file_has_errors {
+            match interpreter::run(&module.items, file.id(), overflow) {
+                Ok(value) => match message_format {
+                    MessageFormat::Human => println!("{}", value),
+                    MessageFormat::Json => eprintln!("{}", value),
+                },
+                Err(error) => {
+                    emit(&Diagnostic::from(&error));
+                    has_errors = true;
+                }
+            }
+        }
+    }
+
+    if has_errors {
+        process::exit(1);
+    }
+}
+
+/// Parses and checks a file, `verbose` prints the tokens and syntax tree.
+fn compile_file(file: &SourceFile, verbose: bool) -> (ast::Module, Vec<Diagnostic>) {
+
+    let (tokens, mut errors) = lexer::lex_file(file);
+
+    if verbose {
+        for token in &tokens {
+            dump!(token);
+        }
+    }
+
+    let (module, parse_errors) = parser::program(tokens.as_slice());
+    errors.extend(parse_errors);
+
+    if verbose {
+        for doc in &module.docs {
+            dump!(doc);
+        }
+        for item in &module.items {
+            dump!(item);
+        }
+    }
 
-    dump!(expression);
+    let mut diagnostics: Vec<_> = errors.iter().map(Diagnostic::from).collect();
+    diagnostics.extend(semantic::check_program(&module.items));
+    (module, diagnostics)
 }
@@ -1,15 +1,105 @@
+use simplelang::*;
+
+/// An expression together with the source range it was parsed from.
+#[derive(Debug)]
+pub struct ExpressionNode {
+    kind: ExpressionKind,
+    span: Span,
+}
+
+impl ExpressionNode {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        ExpressionNode { kind, span }
+    }
+
+    pub fn kind(&self) -> &ExpressionKind {
+        &self.kind
+    }
+
+    pub fn into_kind(self) -> ExpressionKind {
+        self.kind
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Whether the expression denotes a memory location which can be assigned
+    /// to. Error nodes count as places to avoid follow-up errors.
+    pub fn is_place(&self) -> bool {
+        matches!(
+            self.kind,
+            ExpressionKind::Variable(_) |
+            ExpressionKind::Index(..) |
+            ExpressionKind::Field(..) |
+            ExpressionKind::Error
+        )
+    }
+}
+
+/// Spans are not compared, two nodes are equal if their trees are equal.
+impl PartialEq for ExpressionNode {
+    fn eq(&self, other: &ExpressionNode) -> bool {
+        self.kind == other.kind
+    }
+}
+
 #[derive(Debug, PartialEq)]
-pub enum ExpressionNode {
+pub enum ExpressionKind {
+    /// Placeholder for an expression which could not be parsed.
+    Error,
     Integer(i32),
     String(String),
+    /// A string literal with interpolations, like `"a ${b} c"`.
+    Interpolated(Vec<StringPart>),
+    /// Reference to a variable by its name.
+    Variable(String),
     Binary(BinaryOperator, Box<ExpressionNode>, Box<ExpressionNode>),
     Unary(UnaryOperator, Box<ExpressionNode>),
+    /// `callee(arguments)`
+    Call(Box<ExpressionNode>, Vec<ExpressionNode>),
+    /// `target[index]`
+    Index(Box<ExpressionNode>, Box<ExpressionNode>),
+    /// `target.field`
+    Field(Box<ExpressionNode>, String),
+    Block(Block),
+    /// `if condition { .. } else ..`, the else branch is either a block or
+    /// another if expression.
+    If(Box<ExpressionNode>, Block, Option<Box<ExpressionNode>>),
+    /// `target = value` or, with an operator, a compound assignment such as
+    /// `target += value`.
+    Assign(Option<BinaryOperator>, Box<ExpressionNode>, Box<ExpressionNode>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StringPart {
+    /// Text between interpolations, empty text is left out.
+    Text(String),
+    /// The expression of an interpolation `${expression}`.
+    Expression(ExpressionNode),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BinaryOperator {
+    Pow,
     Mul,
+    Div,
+    Rem,
     Add,
+    Sub,
+    Shl,
+    Shr,
+    BitAnd,
+    BitXor,
+    BitOr,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -20,4 +110,113 @@ pub enum UnaryOperator {
     PostInc,
     PreDec,
     PostDec,
+    Not,
+    BitNot,
+}
+
+impl UnaryOperator {
+    /// Whether the operator writes back to its operand, like `++` and `--`.
+    pub fn modifies_operand(self) -> bool {
+        matches!(
+            self,
+            UnaryOperator::PreInc |
+            UnaryOperator::PostInc |
+            UnaryOperator::PreDec |
+            UnaryOperator::PostDec
+        )
+    }
+}
+
+/// The items of a source file.
+#[derive(Debug)]
+pub struct Module {
+    /// Lines of the `//!` doc comments at the start of the file, without
+    /// the `//!`.
+    pub docs: Vec<String>,
+    pub items: Vec<Item>,
+}
+
+/// A top-level declaration of a program.
+#[derive(Debug)]
+pub enum Item {
+    Function(FunctionDecl),
+}
+
+/// `fn name(a, b) { body }`
+#[derive(Debug)]
+pub struct FunctionDecl {
+    /// Lines of the `///` doc comments in front of the declaration, without
+    /// the `///`.
+    pub docs: Vec<String>,
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+    pub body: Block,
+    /// Covers the whole declaration from `fn` up to the closing brace.
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct Parameter {
+    pub name: String,
+    pub span: Span,
+}
+
+/// A statement together with the source range it was parsed from.
+#[derive(Debug)]
+pub struct StatementNode {
+    kind: Statement,
+    span: Span,
+}
+
+impl StatementNode {
+    pub fn new(kind: Statement, span: Span) -> Self {
+        StatementNode { kind, span }
+    }
+
+    pub fn kind(&self) -> &Statement {
+        &self.kind
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Spans are not compared, like for `ExpressionNode`.
+impl PartialEq for StatementNode {
+    fn eq(&self, other: &StatementNode) -> bool {
+        self.kind == other.kind
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Statement {
+    /// `let name = value;`, the initializer is optional.
+    Let(String, Option<ExpressionNode>),
+    /// An expression evaluated for its side effects. It is followed by a `;`
+    /// unless it ends in a block, like `if` and `{ .. }`.
+    Expression(ExpressionNode),
+    While(ExpressionNode, Block),
+    Loop(Block),
+    Break,
+    Continue,
+    Return(Option<ExpressionNode>),
+}
+
+/// `{ statements value }`, the statements share a scope.
+#[derive(Debug)]
+pub struct Block {
+    pub statements: Vec<StatementNode>,
+    /// The final expression without a `;`, which the block evaluates to.
+    /// Blocks without one evaluate to nothing.
+    pub value: Option<Box<ExpressionNode>>,
+    /// Covers the braces.
+    pub span: Span,
+}
+
+/// Spans are not compared, like for `ExpressionNode`.
+impl PartialEq for Block {
+    fn eq(&self, other: &Block) -> bool {
+        self.statements == other.statements && self.value == other.value
+    }
 }
@@ -0,0 +1,564 @@
+use std::fmt;
+use std::fmt::Write;
+use simplelang::*;
+use simplelang::interpreter::RuntimeError;
+use simplelang::source_map::*;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Stable identifier of a kind of diagnostic, the codes are listed at
+/// `to_json`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorCode {
+    // lexer
+    UnexpectedCharacter,
+    UnterminatedString,
+    InvalidEscape,
+    UnterminatedRawString,
+    UnterminatedBlockComment,
+    // parser
+    UnexpectedToken,
+    ChainedOperator,
+    IntegerOutOfRange,
+    InvalidAssignment,
+    MisplacedDocComment,
+    // semantic checks
+    OutsideOfLoop,
+    NotAPlace,
+    // interpreter
+    InvalidExpression,
+    MismatchedTypes,
+    UnknownVariable,
+    UnknownFunction,
+    WrongArgumentCount,
+    DivisionByZero,
+    IntegerOverflow,
+    NegativeExponent,
+    InvalidMain,
+    RecursionLimit,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ErrorCode::UnexpectedCharacter => "E0001",
+            ErrorCode::UnterminatedString => "E0002",
+            ErrorCode::InvalidEscape => "E0003",
+            ErrorCode::UnterminatedRawString => "E0004",
+            ErrorCode::UnterminatedBlockComment => "E0005",
+            ErrorCode::UnexpectedToken => "E0100",
+            ErrorCode::ChainedOperator => "E0101",
+            ErrorCode::IntegerOutOfRange => "E0102",
+            ErrorCode::InvalidAssignment => "E0103",
+            ErrorCode::MisplacedDocComment => "E0104",
+            ErrorCode::OutsideOfLoop => "E0200",
+            ErrorCode::NotAPlace => "E0201",
+            ErrorCode::InvalidExpression => "E0300",
+            ErrorCode::MismatchedTypes => "E0301",
+            ErrorCode::UnknownVariable => "E0302",
+            ErrorCode::UnknownFunction => "E0303",
+            ErrorCode::WrongArgumentCount => "E0304",
+            ErrorCode::DivisionByZero => "E0305",
+            ErrorCode::IntegerOverflow => "E0306",
+            ErrorCode::NegativeExponent => "E0307",
+            ErrorCode::InvalidMain => "E0308",
+            ErrorCode::RecursionLimit => "E0309",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A secondary span of a diagnostic with an explanation of its relevance.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Label {
+    pub start: TextPosition,
+    pub end: TextPosition,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: ErrorCode,
+    pub message: String,
+    pub start: TextPosition,
+    pub end: TextPosition,
+    /// Text shown next to the carets of the primary span.
+    pub label: Option<String>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+    /// Token kinds which would have been valid where an unexpected token was
+    /// found, empty for other kinds of problems.
+    pub expected: Vec<TokenKind>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        code: ErrorCode,
+        message: String,
+        start: TextPosition,
+        end: TextPosition,
+    ) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            message,
+            start,
+            end,
+            label: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+            expected: Vec::new(),
+        }
+    }
+
+    pub fn error(code: ErrorCode, message: String, start: TextPosition, end: TextPosition) -> Self {
+        Diagnostic::new(Severity::Error, code, message, start, end)
+    }
+
+    pub fn with_label(mut self, message: String) -> Self {
+        self.label = Some(message);
+        self
+    }
+
+    pub fn with_secondary(mut self, start: TextPosition, end: TextPosition, message: String) -> Self {
+        self.labels.push(Label {
+            start,
+            end,
+            message,
+        });
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help.push(help);
+        self
+    }
+}
+
+impl<'a> From<&'a SyntaxError> for Diagnostic {
+    fn from(error: &'a SyntaxError) -> Self {
+        let message = error.message().to_string();
+        let mut diagnostic =
+            Diagnostic::new(error.severity(), error.code(), message, error.start(), error.end());
+        if let Some(label) = error.related() {
+            diagnostic = diagnostic.with_secondary(label.start, label.end, label.message.clone());
+        }
+        diagnostic.expected = error.expected().to_vec();
+        diagnostic
+    }
+}
+
+impl<'a> From<&'a RuntimeError> for Diagnostic {
+    fn from(error: &'a RuntimeError) -> Self {
+        let span = error.span();
+        Diagnostic::error(error.code(), error.message().to_string(), span.start, span.end)
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+
+/// Spans covering more lines than this only show their first and last line,
+/// the lines in between are left out.
+const MAX_SPAN_LINES: usize = 4;
+
+/// Renders diagnostics as text with the offending source lines and carets
+/// underlining the reported spans.
+#[derive(Debug, Clone, Copy)]
+pub struct Renderer {
+    colors: bool,
+}
+
+/// A span which is underlined in a snippet, either the primary one or a label.
+struct Marker<'a> {
+    start: TextPosition,
+    end: TextPosition,
+    primary: bool,
+    message: Option<&'a str>,
+}
+
+impl Renderer {
+    pub fn new(colors: bool) -> Self {
+        Renderer { colors }
+    }
+
+    pub fn render(&self, source_map: &SourceMap, diagnostic: &Diagnostic) -> String {
+
+        let mut out = String::new();
+        let severity_color = self.severity_color(diagnostic.severity);
+
+        let title = format!("{}[{}]", diagnostic.severity, diagnostic.code);
+        writeln!(
+            out,
+            "{}: {}",
+            self.paint(severity_color, &title),
+            self.paint(BOLD, &diagnostic.message)
+        ).unwrap();
+
+        let mut markers = vec![
+            Marker {
+                start: diagnostic.start,
+                end: diagnostic.end,
+                primary: true,
+                message: diagnostic.label.as_ref().map(|s| &s[..]),
+            },
+        ];
+        for label in &diagnostic.labels {
+            markers.push(Marker {
+                start: label.start,
+                end: label.end,
+                primary: false,
+                message: Some(&label.message),
+            });
+        }
+
+        let gutter_width = markers
+            .iter()
+            .map(|m| source_map.resolve(m.end).line.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        // the file of the primary span comes first, other files follow in order
+        let mut files = vec![diagnostic.start.file];
+        for marker in &markers {
+            if !files.contains(&marker.start.file) {
+                files.push(marker.start.file);
+            }
+        }
+
+        for (i, file) in files.iter().enumerate() {
+            let file_markers: Vec<_> = markers.iter().filter(|m| m.start.file == *file).collect();
+            let first = if i == 0 {
+                diagnostic.start
+            } else {
+                file_markers[0].start
+            };
+
+            let location = source_map.resolve(first);
+            writeln!(
+                out,
+                "{}{} {}:{}:{}",
+                " ".repeat(gutter_width),
+                self.paint(BLUE, if i == 0 { "-->" } else { ":::" }),
+                location.file_name,
+                location.line,
+                location.column
+            ).unwrap();
+
+            self.render_snippet(
+                &mut out,
+                source_map,
+                *file,
+                &file_markers,
+                severity_color,
+                gutter_width,
+            );
+        }
+
+        for note in &diagnostic.notes {
+            self.render_footer(&mut out, "note", note, gutter_width);
+        }
+        for help in &diagnostic.help {
+            self.render_footer(&mut out, "help", help, gutter_width);
+        }
+
+        out
+    }
+
+    fn render_snippet(
+        &self,
+        out: &mut String,
+        source_map: &SourceMap,
+        file: FileId,
+        markers: &[&Marker],
+        primary_color: &str,
+        gutter_width: usize,
+    ) {
+
+        let source = source_map.file(file);
+
+        let mut lines: Vec<usize> = Vec::new();
+        for marker in markers {
+            let first = source.line_of(marker.start.index);
+            let last = source.line_of(marker.end.index.max(marker.start.index));
+            let marked_lines = if last - first < MAX_SPAN_LINES {
+                (first..last + 1).collect()
+            } else {
+                vec![first, last]
+            };
+            for line in marked_lines {
+                if !lines.contains(&line) {
+                    lines.push(line);
+                }
+            }
+        }
+        lines.sort();
+
+        let empty_gutter = format!("{} {}", " ".repeat(gutter_width), self.paint(BLUE, "|"));
+        writeln!(out, "{}", empty_gutter).unwrap();
+
+        let mut previous_line = None;
+        for line in lines {
+            if previous_line.is_some_and(|previous| line > previous + 1) {
+                writeln!(out, "{}", self.paint(BLUE, "...")).unwrap();
+            }
+            previous_line = Some(line);
+
+            let line_text = source.line_text(line).unwrap_or("");
+            let line_number = format!("{:>width$} |", line + 1, width = gutter_width);
+            writeln!(out, "{} {}", self.paint(BLUE, &line_number), line_text)
+                .unwrap();
+
+            for marker in markers {
+                let (first, last) = match marker_columns(source_map, marker, line, line_text) {
+                    Some(columns) => columns,
+                    None => continue,
+                };
+
+                // copy tabs from the source line so the carets stay aligned
+                let padding: String = line_text
+                    .chars()
+                    .take(first)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                let underline = if marker.primary { "^" } else { "-" };
+                let underline = underline.repeat((last - first).max(1));
+
+                let is_last_line = source.line_of(marker.end.index.max(marker.start.index)) == line;
+                let text = match marker.message {
+                    Some(message) if is_last_line => format!("{} {}", underline, message),
+                    _ => underline,
+                };
+
+                let color = if marker.primary { primary_color } else { BLUE };
+                writeln!(out, "{} {}{}", empty_gutter, padding, self.paint(color, &text))
+                    .unwrap();
+            }
+        }
+
+        writeln!(out, "{}", empty_gutter).unwrap();
+    }
+
+    fn render_footer(&self, out: &mut String, kind: &str, message: &str, gutter_width: usize) {
+        let color = if kind == "help" { CYAN } else { BOLD };
+        writeln!(
+            out,
+            "{} {} {}: {}",
+            " ".repeat(gutter_width),
+            self.paint(BLUE, "="),
+            self.paint(color, kind),
+            message
+        ).unwrap();
+    }
+
+    fn severity_color(&self, severity: Severity) -> &'static str {
+        match severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        }
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.colors && !text.is_empty() {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Returns the character columns `first..last` which `marker` covers on
+/// `line`, or `None` if the marker does not touch that line.
+fn marker_columns(
+    source_map: &SourceMap,
+    marker: &Marker,
+    line: usize,
+    line_text: &str,
+) -> Option<(usize, usize)> {
+
+    let start = source_map.resolve(marker.start);
+    let end = source_map.resolve(marker.end);
+    let line = line + 1;
+    let line_length = line_text.chars().count();
+
+    if line < start.line || line > end.line.max(start.line) {
+        return None;
+    }
+
+    let first = if line == start.line { start.column - 1 } else { 0 };
+    let last = if line == end.line {
+        end.column - 1
+    } else {
+        line_length.max(first + 1)
+    };
+
+    Some((first, last.max(first)))
+}
+
+/// Serializes a diagnostic as a single line JSON object.
+///
+/// The schema is stable, fields are only ever added:
+///
+/// ```text
+/// {
+///   "severity": "error" | "warning",
+///   "code": string,
+///   "message": string,
+///   "file": string,
+///   "start": { "offset": number, "line": number, "column": number },
+///   "end": { "offset": number, "line": number, "column": number },
+///   "label": string | null,
+///   "related": [
+///     { "file": string, "start": {...}, "end": {...}, "message": string }
+///   ],
+///   "notes": [string],
+///   "help": [string],
+///   "expected": [string]
+/// }
+/// ```
+///
+/// `offset` is the byte offset into the file, `line` and `column` are
+/// one-based and count characters.
+///
+/// Every diagnostic has a `code` which identifies the kind of problem and
+/// never changes, even when the wording of the message does:
+///
+/// | Code  | Stage    | Problem                                             |
+/// |-------|----------|-----------------------------------------------------|
+/// | E0001 | lexer    | unexpected character                                |
+/// | E0002 | lexer    | unterminated string literal                         |
+/// | E0003 | lexer    | invalid escape sequence                             |
+/// | E0004 | lexer    | unterminated raw string literal                     |
+/// | E0005 | lexer    | unterminated block comment                          |
+/// | E0100 | parser   | unexpected token                                    |
+/// | E0101 | parser   | chained non-associative operators                   |
+/// | E0102 | parser   | integer literal out of range                        |
+/// | E0103 | parser   | invalid assignment target                           |
+/// | E0104 | parser   | misplaced doc comment                               |
+/// | E0200 | semantic | `break` or `continue` outside of a loop             |
+/// | E0201 | semantic | `++` or `--` applied to something but a variable    |
+/// | E0300 | runtime  | evaluation of an expression which failed to parse   |
+/// | E0301 | runtime  | operation on a value of the wrong type              |
+/// | E0302 | runtime  | unknown variable                                    |
+/// | E0303 | runtime  | unknown function or call of something else          |
+/// | E0304 | runtime  | wrong number of arguments                           |
+/// | E0305 | runtime  | division by zero                                    |
+/// | E0306 | runtime  | integer overflow                                    |
+/// | E0307 | runtime  | negative exponent in integer power                  |
+/// | E0308 | runtime  | missing or invalid `main` function                  |
+/// | E0309 | runtime  | function calls nested too deeply                    |
+///
+/// `related` lists further spans which explain the problem, like where an
+/// unterminated literal was opened. `expected` lists the tokens which would
+/// have been valid instead of an unexpected token, like `"';'"` or
+/// `"identifier"`.
+pub fn to_json(source_map: &SourceMap, diagnostic: &Diagnostic) -> String {
+
+    let mut out = String::new();
+
+    out.push('{');
+    write!(out, "\"severity\":{}", json_string(&diagnostic.severity.to_string())).unwrap();
+    write!(out, ",\"code\":{}", json_string(diagnostic.code.as_str())).unwrap();
+    write!(out, ",\"message\":{}", json_string(&diagnostic.message)).unwrap();
+    out.push(',');
+    write_json_span(&mut out, source_map, diagnostic.start, diagnostic.end);
+    write!(out, ",\"label\":{}", json_option(&diagnostic.label)).unwrap();
+
+    out.push_str(",\"related\":[");
+    for (i, label) in diagnostic.labels.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('{');
+        write_json_span(&mut out, source_map, label.start, label.end);
+        write!(out, ",\"message\":{}", json_string(&label.message)).unwrap();
+        out.push('}');
+    }
+    out.push(']');
+
+    write!(out, ",\"notes\":{}", json_array(&diagnostic.notes)).unwrap();
+    write!(out, ",\"help\":{}", json_array(&diagnostic.help)).unwrap();
+    let expected: Vec<_> = diagnostic.expected.iter().map(|kind| kind.to_string()).collect();
+    write!(out, ",\"expected\":{}", json_array(&expected)).unwrap();
+    out.push('}');
+
+    out
+}
+
+fn write_json_span(out: &mut String, source_map: &SourceMap, start: TextPosition, end: TextPosition) {
+
+    let file_name = source_map.file(start.file).name();
+    write!(out, "\"file\":{}", json_string(file_name)).unwrap();
+    write!(out, ",\"start\":{}", json_position(source_map, start)).unwrap();
+    write!(out, ",\"end\":{}", json_position(source_map, end)).unwrap();
+}
+
+fn json_position(source_map: &SourceMap, position: TextPosition) -> String {
+    let location = source_map.resolve(position);
+    format!(
+        "{{\"offset\":{},\"line\":{},\"column\":{}}}",
+        position.index,
+        location.line,
+        location.column
+    )
+}
+
+fn json_option(value: &Option<String>) -> String {
+    match *value {
+        Some(ref value) => json_string(value),
+        None => "null".to_string(),
+    }
+}
+
+fn json_array(values: &[String]) -> String {
+    let values: Vec<_> = values.iter().map(|v| json_string(v)).collect();
+    format!("[{}]", values.join(","))
+}
+
+fn json_string(value: &str) -> String {
+
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
@@ -0,0 +1,36 @@
+use std::fmt::Write;
+use simplelang::ast::*;
+
+/// Renders the doc comments of a module and of its functions as Markdown,
+/// with a heading for the module `name` and one for every function.
+pub fn render_markdown(name: &str, module: &Module) -> String {
+
+    let mut out = format!("# {}\n", name);
+    write_docs(&mut out, &module.docs);
+
+    for item in &module.items {
+        match *item {
+            Item::Function(ref function) => {
+                let parameters: Vec<_> = function.parameters.iter().map(|p| &p.name[..]).collect();
+                write!(out, "\n## fn {}({})\n", function.name, parameters.join(", ")).unwrap();
+                write_docs(&mut out, &function.docs);
+            }
+        }
+    }
+
+    out
+}
+
+/// Writes doc comment lines as a paragraph, the single space which usually
+/// follows `///` is not part of the text.
+fn write_docs(out: &mut String, docs: &[String]) {
+
+    if docs.is_empty() {
+        return;
+    }
+
+    out.push('\n');
+    for line in docs {
+        writeln!(out, "{}", line.strip_prefix(' ').unwrap_or(line)).unwrap();
+    }
+}
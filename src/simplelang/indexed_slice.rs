@@ -31,10 +31,7 @@ impl<'a, T> IndexedSlice<'a, T> {
     }
 
     pub fn try_get(self, index: usize) -> Option<&'a T> {
-        match self.len() {
-            0 | 1 => None,
-            _ => Some(&self.slice[index]),
-        }
+        self.slice.get(index)
     }
 
     pub fn position(&self) -> usize {
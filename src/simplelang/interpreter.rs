@@ -0,0 +1,694 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::panic;
+use std::thread;
+use simplelang::*;
+use simplelang::ast::*;
+use simplelang::source_map::FileId;
+use simplelang::diagnostics::ErrorCode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i32),
+    String(String),
+    Boolean(bool),
+    /// The value of expressions which produce nothing, like assignments and
+    /// blocks without a final expression.
+    Unit,
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Value::Integer(_) => "integer",
+            Value::String(_) => "string",
+            Value::Boolean(_) => "boolean",
+            Value::Unit => "unit",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::String(ref value) => f.write_str(value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Unit => f.write_str("()"),
+        }
+    }
+}
+
+pub type EvalResult<T> = Result<T, RuntimeError>;
+
+/// What happens when the result of integer arithmetic does not fit into an
+/// integer.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Overflow {
+    /// Stops the program with an error located at the operation.
+    #[default]
+    Checked,
+    /// Wraps around at the bounds, as two's complement arithmetic does.
+    Wrapping,
+    /// Clamps the result to the smallest or largest integer.
+    Saturating,
+}
+
+impl Overflow {
+    /// Picks the result of an operation for this mode, `None` if a checked
+    /// operation overflowed.
+    fn select(self, checked: Option<i32>, wrapping: i32, saturating: i32) -> Option<i32> {
+        match self {
+            Overflow::Checked => checked,
+            Overflow::Wrapping => Some(wrapping),
+            Overflow::Saturating => Some(saturating),
+        }
+    }
+}
+
+/// An error which stops the evaluation, located at the expression which
+/// caused it.
+#[derive(Debug)]
+pub struct RuntimeError {
+    /// Stable identifier of the kind of error, see `diagnostics::to_json`.
+    code: ErrorCode,
+    message: String,
+    span: Span,
+}
+
+impl RuntimeError {
+    fn new(code: ErrorCode, message: String, span: Span) -> Self {
+        RuntimeError {
+            code,
+            message,
+            span,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Evaluates a single expression without any variables or functions.
+pub fn eval(expr: &ExpressionNode, overflow: Overflow) -> EvalResult<Value> {
+    let mut interpreter = Interpreter::new(&[], overflow);
+    interpreter.scopes.push(HashMap::new());
+    match interpreter.eval(expr) {
+        Err(Unwind::Return(value)) => Ok(value),
+        result => result.map_err(Unwind::into_error),
+    }
+}
+
+/// Runs a program by calling its `main` function, which must not take any
+/// parameters, and returns the value `main` returned. A missing `main` is
+/// reported at the start of `file`, the file the program was parsed from.
+///
+/// The program runs on a thread of its own, with a stack large enough for
+/// `MAX_CALL_DEPTH` nested calls.
+pub fn run(items: &[Item], file: FileId, overflow: Overflow) -> EvalResult<Value> {
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, || run_main(items, file, overflow))
+            .expect("unable to start the interpreter thread")
+            .join()
+            .unwrap_or_else(|panic| panic::resume_unwind(panic))
+    })
+}
+
+fn run_main(items: &[Item], file: FileId, overflow: Overflow) -> EvalResult<Value> {
+    let mut interpreter = Interpreter::new(items, overflow);
+
+    let main = match interpreter.functions.get("main") {
+        Some(main) => *main,
+        None => {
+            let start = TextPosition {
+                file,
+                ..TextPosition::default()
+            };
+            let span = Span::new(start, start);
+            let message = "No 'main' function found".to_string();
+            return Err(RuntimeError::new(ErrorCode::InvalidMain, message, span));
+        }
+    };
+
+    if let (Some(first), Some(last)) = (main.parameters.first(), main.parameters.last()) {
+        let message = "The 'main' function must not take parameters".to_string();
+        let span = Span::new(first.span.start, last.span.end);
+        return Err(RuntimeError::new(ErrorCode::InvalidMain, message, span));
+    }
+
+    interpreter.call(main, Vec::new()).map_err(Unwind::into_error)
+}
+
+/// Leaves the expressions being evaluated up to the construct which handles
+/// it, like a loop for `break` or the function call for `return`.
+enum Unwind {
+    Error(RuntimeError),
+    Break(Span),
+    Continue(Span),
+    Return(Value),
+}
+
+impl Unwind {
+    fn into_error(self) -> RuntimeError {
+        match self {
+            Unwind::Error(error) => error,
+            Unwind::Break(span) => {
+                let message = "'break' outside of a loop".to_string();
+                RuntimeError::new(ErrorCode::OutsideOfLoop, message, span)
+            }
+            Unwind::Continue(span) => {
+                let message = "'continue' outside of a loop".to_string();
+                RuntimeError::new(ErrorCode::OutsideOfLoop, message, span)
+            }
+            Unwind::Return(_) => unreachable!("'return' is handled by the function call"),
+        }
+    }
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+type Exec<T> = Result<T, Unwind>;
+
+/// Deepest nesting of function calls, deeper recursion is reported as an
+/// error instead of overflowing the stack of the interpreter.
+const MAX_CALL_DEPTH: usize = 1000;
+
+/// Stack size of the thread `run` evaluates on. A call takes up to about
+/// 100 KiB of stack in unoptimized builds, so this leaves ample room.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+struct Interpreter<'a> {
+    functions: HashMap<&'a str, &'a FunctionDecl>,
+    /// Variables of the function being executed, one map per nested block.
+    scopes: Vec<HashMap<String, Value>>,
+    overflow: Overflow,
+    /// Number of function calls which have not returned yet.
+    call_depth: usize,
+}
+
+impl<'a> Interpreter<'a> {
+    fn new(items: &'a [Item], overflow: Overflow) -> Self {
+
+        let mut functions = HashMap::new();
+        for item in items {
+            match *item {
+                Item::Function(ref function) => {
+                    functions.insert(&function.name[..], function);
+                }
+            }
+        }
+
+        Interpreter {
+            functions,
+            scopes: Vec::new(),
+            overflow,
+            call_depth: 0,
+        }
+    }
+
+    fn call(&mut self, function: &'a FunctionDecl, arguments: Vec<Value>) -> Exec<Value> {
+
+        let mut scope = HashMap::new();
+        for (parameter, argument) in function.parameters.iter().zip(arguments) {
+            scope.insert(parameter.name.clone(), argument);
+        }
+
+        // the callee cannot see the variables of the caller
+        let caller_scopes = ::std::mem::replace(&mut self.scopes, vec![scope]);
+        let result = self.eval_block(&function.body);
+        self.scopes = caller_scopes;
+
+        match result {
+            Err(Unwind::Return(value)) => Ok(value),
+            result => result,
+        }
+    }
+
+    fn eval_block(&mut self, block: &Block) -> Exec<Value> {
+        self.scopes.push(HashMap::new());
+        let result = self.eval_block_in_scope(block);
+        self.scopes.pop();
+        result
+    }
+
+    fn eval_block_in_scope(&mut self, block: &Block) -> Exec<Value> {
+        for statement in &block.statements {
+            self.exec(statement)?;
+        }
+        match block.value {
+            Some(ref value) => self.eval(value),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    fn exec(&mut self, statement: &StatementNode) -> Exec<()> {
+        match *statement.kind() {
+            Statement::Let(ref name, ref value) => {
+                let value = match *value {
+                    Some(ref value) => self.eval(value)?,
+                    None => Value::Unit,
+                };
+                let scope = self.scopes.last_mut().expect("a block must be entered");
+                scope.insert(name.clone(), value);
+            }
+            Statement::Expression(ref expr) => {
+                self.eval(expr)?;
+            }
+            Statement::While(ref condition, ref body) => {
+                while self.eval_condition(condition)? {
+                    match self.eval_block(body) {
+                        Err(Unwind::Break(_)) => break,
+                        Err(Unwind::Continue(_)) | Ok(_) => {}
+                        Err(unwind) => return Err(unwind),
+                    }
+                }
+            }
+            Statement::Loop(ref body) => {
+                loop {
+                    match self.eval_block(body) {
+                        Err(Unwind::Break(_)) => break,
+                        Err(Unwind::Continue(_)) | Ok(_) => {}
+                        Err(unwind) => return Err(unwind),
+                    }
+                }
+            }
+            Statement::Break => return Err(Unwind::Break(statement.span())),
+            Statement::Continue => return Err(Unwind::Continue(statement.span())),
+            Statement::Return(ref value) => {
+                let value = match *value {
+                    Some(ref value) => self.eval(value)?,
+                    None => Value::Unit,
+                };
+                return Err(Unwind::Return(value));
+            }
+        }
+        Ok(())
+    }
+
+    fn eval(&mut self, expr: &ExpressionNode) -> Exec<Value> {
+        match *expr.kind() {
+            ExpressionKind::Error => {
+                let message = "Cannot evaluate an invalid expression".to_string();
+                Err(RuntimeError::new(ErrorCode::InvalidExpression, message, expr.span()).into())
+            }
+            ExpressionKind::Integer(value) => Ok(Value::Integer(value)),
+            ExpressionKind::String(ref value) => Ok(Value::String(value.clone())),
+            ExpressionKind::Interpolated(ref parts) => {
+                let mut result = String::new();
+                for part in parts {
+                    match *part {
+                        StringPart::Text(ref text) => result.push_str(text),
+                        StringPart::Expression(ref expr) => {
+                            result.push_str(&self.eval(expr)?.to_string());
+                        }
+                    }
+                }
+                Ok(Value::String(result))
+            }
+            ExpressionKind::Variable(ref name) => {
+                match self.lookup(name) {
+                    Some(value) => Ok(value.clone()),
+                    None => Err(undefined_variable(name, expr.span()).into()),
+                }
+            }
+            ExpressionKind::Binary(BinaryOperator::And, ref lhs, ref rhs) => {
+                Ok(Value::Boolean(
+                    self.eval_condition(lhs)? && self.eval_condition(rhs)?,
+                ))
+            }
+            ExpressionKind::Binary(BinaryOperator::Or, ref lhs, ref rhs) => {
+                Ok(Value::Boolean(
+                    self.eval_condition(lhs)? || self.eval_condition(rhs)?,
+                ))
+            }
+            ExpressionKind::Binary(operator, ref lhs, ref rhs) => {
+                let lhs = self.eval(lhs)?;
+                let rhs = self.eval(rhs)?;
+                Ok(binary_operation(operator, lhs, rhs, self.overflow, expr.span())?)
+            }
+            ExpressionKind::Unary(operator, ref operand) => self.eval_unary(operator, operand, expr),
+            ExpressionKind::Assign(operator, ref target, ref value) => {
+                let value = self.eval(value)?;
+                let value = match operator {
+                    Some(operator) => {
+                        let current = self.eval(target)?;
+                        binary_operation(operator, current, value, self.overflow, expr.span())?
+                    }
+                    None => value,
+                };
+                self.store(target, value)?;
+                Ok(Value::Unit)
+            }
+            ExpressionKind::Call(ref callee, ref arguments) => self.eval_call(callee, arguments, expr),
+            ExpressionKind::Index(ref target, _) => {
+                let target = self.eval(target)?;
+                let message = format!("Cannot index into a value of type {}", target.type_name());
+                Err(RuntimeError::new(ErrorCode::MismatchedTypes, message, expr.span()).into())
+            }
+            ExpressionKind::Field(ref target, ref name) => {
+                let target = self.eval(target)?;
+                let message = format!("Type {} has no field '{}'", target.type_name(), name);
+                Err(RuntimeError::new(ErrorCode::MismatchedTypes, message, expr.span()).into())
+            }
+            ExpressionKind::Block(ref block) => self.eval_block(block),
+            ExpressionKind::If(ref condition, ref then_block, ref else_branch) => {
+                if self.eval_condition(condition)? {
+                    self.eval_block(then_block)
+                } else {
+                    match *else_branch {
+                        Some(ref else_branch) => self.eval(else_branch),
+                        None => Ok(Value::Unit),
+                    }
+                }
+            }
+        }
+    }
+
+    fn eval_unary(
+        &mut self,
+        operator: UnaryOperator,
+        operand: &ExpressionNode,
+        expr: &ExpressionNode,
+    ) -> Exec<Value> {
+
+        let value = self.eval(operand)?;
+        let overflow = self.overflow;
+        let overflow_error = || {
+            let message = overflow_message(unary_symbol(operator));
+            RuntimeError::new(ErrorCode::IntegerOverflow, message, expr.span())
+        };
+
+        let result = match (operator, value) {
+            (UnaryOperator::Plus, Value::Integer(value)) => Value::Integer(value),
+            (UnaryOperator::Negate, Value::Integer(value)) => {
+                let result = overflow.select(
+                    value.checked_neg(),
+                    value.wrapping_neg(),
+                    value.saturating_neg(),
+                );
+                Value::Integer(result.ok_or_else(overflow_error)?)
+            }
+            (UnaryOperator::BitNot, Value::Integer(value)) => Value::Integer(!value),
+            (UnaryOperator::Not, Value::Boolean(value)) => Value::Boolean(!value),
+            (UnaryOperator::PreInc, Value::Integer(value)) |
+            (UnaryOperator::PostInc, Value::Integer(value)) |
+            (UnaryOperator::PreDec, Value::Integer(value)) |
+            (UnaryOperator::PostDec, Value::Integer(value)) => {
+                let new_value = match operator {
+                    UnaryOperator::PreInc | UnaryOperator::PostInc => {
+                        overflow.select(
+                            value.checked_add(1),
+                            value.wrapping_add(1),
+                            value.saturating_add(1),
+                        )
+                    }
+                    _ => {
+                        overflow.select(
+                            value.checked_sub(1),
+                            value.wrapping_sub(1),
+                            value.saturating_sub(1),
+                        )
+                    }
+                };
+                let new_value = new_value.ok_or_else(overflow_error)?;
+                self.store(operand, Value::Integer(new_value))?;
+
+                match operator {
+                    UnaryOperator::PostInc | UnaryOperator::PostDec => Value::Integer(value),
+                    _ => Value::Integer(new_value),
+                }
+            }
+            (_, value) => {
+                let message = format!(
+                    "Cannot apply {} to a value of type {}",
+                    unary_symbol(operator),
+                    value.type_name()
+                );
+                let error = RuntimeError::new(ErrorCode::MismatchedTypes, message, expr.span());
+                return Err(error.into());
+            }
+        };
+
+        Ok(result)
+    }
+
+    fn eval_call(
+        &mut self,
+        callee: &ExpressionNode,
+        arguments: &[ExpressionNode],
+        expr: &ExpressionNode,
+    ) -> Exec<Value> {
+
+        let function = match *callee.kind() {
+            ExpressionKind::Variable(ref name) => {
+                match self.functions.get(&name[..]) {
+                    Some(function) => *function,
+                    None => {
+                        let message = format!("Cannot find function '{}'", name);
+                        let error = RuntimeError::new(ErrorCode::UnknownFunction, message, callee.span());
+                        return Err(error.into());
+                    }
+                }
+            }
+            _ => {
+                let message = "Only functions can be called".to_string();
+                let error = RuntimeError::new(ErrorCode::UnknownFunction, message, callee.span());
+                return Err(error.into());
+            }
+        };
+
+        if function.parameters.len() != arguments.len() {
+            let message = format!(
+                "Function '{}' takes {} argument(s) but {} were given",
+                function.name,
+                function.parameters.len(),
+                arguments.len()
+            );
+            let error = RuntimeError::new(ErrorCode::WrongArgumentCount, message, expr.span());
+            return Err(error.into());
+        }
+
+        let mut values = Vec::new();
+        for argument in arguments {
+            values.push(self.eval(argument)?);
+        }
+
+        if self.call_depth >= MAX_CALL_DEPTH {
+            let message = format!(
+                "Recursion limit exceeded, calls may nest at most {} deep",
+                MAX_CALL_DEPTH
+            );
+            let error = RuntimeError::new(ErrorCode::RecursionLimit, message, expr.span());
+            return Err(error.into());
+        }
+
+        self.call_depth += 1;
+        let result = self.call(function, values);
+        self.call_depth -= 1;
+        result
+    }
+
+    /// Evaluates an expression which has to result in a boolean.
+    fn eval_condition(&mut self, expr: &ExpressionNode) -> Exec<bool> {
+        match self.eval(expr)? {
+            Value::Boolean(value) => Ok(value),
+            value => {
+                let message = format!("Expected a boolean but found {}", value.type_name());
+                Err(RuntimeError::new(ErrorCode::MismatchedTypes, message, expr.span()).into())
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Value> {
+        self.scopes.iter().rev().filter_map(|scope| scope.get(name)).next()
+    }
+
+    /// Assigns to the variable `target` refers to.
+    fn store(&mut self, target: &ExpressionNode, value: Value) -> Exec<()> {
+        match *target.kind() {
+            ExpressionKind::Variable(ref name) => {
+                for scope in self.scopes.iter_mut().rev() {
+                    if let Some(variable) = scope.get_mut(name) {
+                        *variable = value;
+                        return Ok(());
+                    }
+                }
+                Err(undefined_variable(name, target.span()).into())
+            }
+            _ => {
+                // the target has been evaluated before, so indexing and
+                // field access have already failed
+                let message = "Cannot assign to this expression".to_string();
+                Err(RuntimeError::new(ErrorCode::InvalidAssignment, message, target.span()).into())
+            }
+        }
+    }
+}
+
+fn binary_operation(
+    operator: BinaryOperator,
+    lhs: Value,
+    rhs: Value,
+    overflow: Overflow,
+    span: Span,
+) -> EvalResult<Value> {
+
+    use simplelang::ast::BinaryOperator::*;
+    use simplelang::interpreter::Value::*;
+
+    let result = match (operator, lhs, rhs) {
+        (Div, Integer(_), Integer(0)) |
+        (Rem, Integer(_), Integer(0)) => {
+            let message = "Division by zero".to_string();
+            return Err(RuntimeError::new(ErrorCode::DivisionByZero, message, span));
+        }
+        (Pow, Integer(_), Integer(b)) if b < 0 => {
+            let message = "Negative exponent in integer power".to_string();
+            return Err(RuntimeError::new(ErrorCode::NegativeExponent, message, span));
+        }
+        (Add, Integer(a), Integer(b)) |
+        (Sub, Integer(a), Integer(b)) |
+        (Mul, Integer(a), Integer(b)) |
+        (Div, Integer(a), Integer(b)) |
+        (Rem, Integer(a), Integer(b)) |
+        (Pow, Integer(a), Integer(b)) |
+        (Shl, Integer(a), Integer(b)) |
+        (Shr, Integer(a), Integer(b)) => {
+            match integer_arithmetic(operator, a, b, overflow) {
+                Some(result) => Integer(result),
+                None => {
+                    let message = overflow_message(binary_symbol(operator));
+                    return Err(RuntimeError::new(ErrorCode::IntegerOverflow, message, span));
+                }
+            }
+        }
+        (BitAnd, Integer(a), Integer(b)) => Integer(a & b),
+        (BitXor, Integer(a), Integer(b)) => Integer(a ^ b),
+        (BitOr, Integer(a), Integer(b)) => Integer(a | b),
+        (BitAnd, Boolean(a), Boolean(b)) => Boolean(a & b),
+        (BitXor, Boolean(a), Boolean(b)) => Boolean(a ^ b),
+        (BitOr, Boolean(a), Boolean(b)) => Boolean(a | b),
+        (Add, String(a), String(b)) => String(a + &b),
+        (Less, Integer(a), Integer(b)) => Boolean(a < b),
+        (LessEq, Integer(a), Integer(b)) => Boolean(a <= b),
+        (Greater, Integer(a), Integer(b)) => Boolean(a > b),
+        (GreaterEq, Integer(a), Integer(b)) => Boolean(a >= b),
+        (Less, String(a), String(b)) => Boolean(a < b),
+        (LessEq, String(a), String(b)) => Boolean(a <= b),
+        (Greater, String(a), String(b)) => Boolean(a > b),
+        (GreaterEq, String(a), String(b)) => Boolean(a >= b),
+        (Eq, ref a, ref b) if a.type_name() == b.type_name() => Boolean(a == b),
+        (NotEq, ref a, ref b) if a.type_name() == b.type_name() => Boolean(a != b),
+        (_, lhs, rhs) => {
+            let message = format!(
+                "Cannot apply {} to values of type {} and {}",
+                binary_symbol(operator),
+                lhs.type_name(),
+                rhs.type_name()
+            );
+            return Err(RuntimeError::new(ErrorCode::MismatchedTypes, message, span));
+        }
+    };
+
+    Ok(result)
+}
+
+/// Applies an arithmetic operator to integers, `None` if the result
+/// overflowed in `Overflow::Checked` mode. Division by zero and negative
+/// exponents must have been ruled out before.
+fn integer_arithmetic(operator: BinaryOperator, a: i32, b: i32, overflow: Overflow) -> Option<i32> {
+    match operator {
+        BinaryOperator::Add => overflow.select(a.checked_add(b), a.wrapping_add(b), a.saturating_add(b)),
+        BinaryOperator::Sub => overflow.select(a.checked_sub(b), a.wrapping_sub(b), a.saturating_sub(b)),
+        BinaryOperator::Mul => overflow.select(a.checked_mul(b), a.wrapping_mul(b), a.saturating_mul(b)),
+        BinaryOperator::Div => overflow.select(a.checked_div(b), a.wrapping_div(b), a.saturating_div(b)),
+        // the remainder always fits, only `i32::MIN % -1` overflows in the
+        // intermediate division
+        BinaryOperator::Rem => Some(a.wrapping_rem(b)),
+        BinaryOperator::Pow => {
+            let b = b as u32;
+            overflow.select(a.checked_pow(b), a.wrapping_pow(b), a.saturating_pow(b))
+        }
+        // a shift overflows if it shifts by 32 bits or more, negative amounts
+        // become too large as well. Saturating shifts behave like multiplying
+        // or dividing by a power of two.
+        BinaryOperator::Shl => {
+            let b = b as u32;
+            overflow.select(a.checked_shl(b), a.wrapping_shl(b), saturating_shl(a, b))
+        }
+        BinaryOperator::Shr => {
+            let b = b as u32;
+            overflow.select(a.checked_shr(b), a.wrapping_shr(b), a >> b.min(31))
+        }
+        _ => unreachable!("{:?} is not an arithmetic operator", operator),
+    }
+}
+
+fn saturating_shl(value: i32, amount: u32) -> i32 {
+    if value == 0 {
+        0
+    } else if amount >= 32 {
+        if value < 0 { i32::MIN } else { i32::MAX }
+    } else {
+        let result = i64::from(value) << amount;
+        result.max(i64::from(i32::MIN)).min(i64::from(i32::MAX)) as i32
+    }
+}
+
+fn overflow_message(symbol: &str) -> String {
+    format!("Integer overflow in {}", symbol)
+}
+
+fn undefined_variable(name: &str, span: Span) -> RuntimeError {
+    RuntimeError::new(ErrorCode::UnknownVariable, format!("Cannot find variable '{}'", name), span)
+}
+
+fn binary_symbol(operator: BinaryOperator) -> &'static str {
+    match operator {
+        BinaryOperator::Pow => "'**'",
+        BinaryOperator::Mul => "'*'",
+        BinaryOperator::Div => "'/'",
+        BinaryOperator::Rem => "'%'",
+        BinaryOperator::Add => "'+'",
+        BinaryOperator::Sub => "'-'",
+        BinaryOperator::Shl => "'<<'",
+        BinaryOperator::Shr => "'>>'",
+        BinaryOperator::BitAnd => "'&'",
+        BinaryOperator::BitXor => "'^'",
+        BinaryOperator::BitOr => "'|'",
+        BinaryOperator::Eq => "'=='",
+        BinaryOperator::NotEq => "'!='",
+        BinaryOperator::Less => "'<'",
+        BinaryOperator::LessEq => "'<='",
+        BinaryOperator::Greater => "'>'",
+        BinaryOperator::GreaterEq => "'>='",
+        BinaryOperator::And => "'&&'",
+        BinaryOperator::Or => "'||'",
+    }
+}
+
+fn unary_symbol(operator: UnaryOperator) -> &'static str {
+    match operator {
+        UnaryOperator::Plus => "'+'",
+        UnaryOperator::Negate => "'-'",
+        UnaryOperator::PreInc | UnaryOperator::PostInc => "'++'",
+        UnaryOperator::PreDec | UnaryOperator::PostDec => "'--'",
+        UnaryOperator::Not => "'!'",
+        UnaryOperator::BitNot => "'~'",
+    }
+}
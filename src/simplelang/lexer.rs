@@ -1,168 +1,693 @@
-use std::io;
-use std::io::BufRead;
 use std::str;
 use std::collections::HashMap;
 use simplelang::*;
 use simplelang::indexed_slice::*;
+use simplelang::source_map::*;
+use simplelang::diagnostics::ErrorCode;
+
+/// Cursor over the source characters which keeps track of the `TextPosition`
+/// of the next character while it is advanced.
+#[derive(Clone, Copy)]
+struct LexSlice<'a> {
+    chars: IndexedSlice<'a, char>,
+    position: TextPosition,
+}
+
+impl<'a> LexSlice<'a> {
+    fn from_chars(file: FileId, chars: &'a [char]) -> Self {
+        LexSlice {
+            chars: IndexedSlice::from_slice(chars),
+            position: TextPosition {
+                file,
+                index: 0,
+                line: 0,
+                column: 0,
+            },
+        }
+    }
+
+    fn first(&self) -> Option<&'a char> {
+        self.chars.first()
+    }
+
+    fn try_get(&self, index: usize) -> Option<&'a char> {
+        self.chars.try_get(index)
+    }
 
-type LexSlice<'a> = IndexedSlice<'a, char>;
+    fn pop_first(&mut self) -> Option<&'a char> {
+        let c = self.chars.pop_first()?;
+
+        self.position.index += c.len_utf8();
+        if *c == '\n' {
+            self.position.line += 1;
+            self.position.column = 0;
+        } else {
+            self.position.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn position(&self) -> TextPosition {
+        self.position
+    }
+}
 
 lazy_static! {
 	static ref KEYWORD_MAPPING: HashMap<&'static str, TokenKind> = {
 		map! {
-			"fn" => TokenKind::FnKeyword
+			"fn" => TokenKind::FnKeyword,
+			"let" => TokenKind::LetKeyword,
+			"if" => TokenKind::IfKeyword,
+			"else" => TokenKind::ElseKeyword,
+			"while" => TokenKind::WhileKeyword,
+			"loop" => TokenKind::LoopKeyword,
+			"break" => TokenKind::BreakKeyword,
+			"continue" => TokenKind::ContinueKeyword,
+			"return" => TokenKind::ReturnKeyword
 		}
 	};
 }
 
-pub fn lex<TIn>(input: TIn) -> SyntaxResult<Vec<Token>>
+/// Lexes a source which is not registered in a `SourceMap`. Its tokens
+/// belong to `FileId::default()`.
+#[cfg(test)]
+pub fn lex<TIn>(input: TIn) -> (Vec<Token>, Vec<SyntaxError>)
 where
-    TIn: io::Read,
+    TIn: ::std::io::Read,
 {
-    let input = io::BufReader::new(input);
+    let mut input = input;
+    let mut text = String::new();
+    input.read_to_string(&mut text).expect(
+        "unable to read input file",
+    );
+
+    lex_text(FileId::default(), &text)
+}
+
+/// Lexes a whole file. Invalid input does not stop the lexer, it is turned
+/// into `TokenKind::Error` tokens and reported in the returned error list.
+/// The returned tokens always end with a `TokenKind::EndOfInput` token.
+pub fn lex_file(file: &SourceFile) -> (Vec<Token>, Vec<SyntaxError>) {
+    lex_text(file.id(), file.text())
+}
+
+fn lex_text(file: FileId, text: &str) -> (Vec<Token>, Vec<SyntaxError>) {
+
+    let chars: Vec<_> = text.chars().collect();
+    let mut slice = LexSlice::from_chars(file, &chars);
     let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    lex_tokens(&mut slice, &mut tokens, &mut errors);
 
-    for line in input.lines() {
-        let line: Vec<_> = line.expect("unable to read input file").chars().collect();
-        let mut slice = IndexedSlice::from_chars(&line);
-        lex_line(&mut slice, &mut tokens)?;
-    }
+    (tokens, errors)
+}
 
-    return Ok(tokens);
+/// An interpolation `${...}` within a string literal which is being lexed.
+struct Interpolation {
+    /// Position of the quote which opened the string literal.
+    quote: TextPosition,
+    /// Number of braces opened within the interpolation and not yet closed.
+    depth: usize,
 }
 
-fn lex_line(line: &mut LexSlice, result: &mut Vec<Token>) -> SyntaxResult<()> {
+fn lex_tokens(slice: &mut LexSlice, result: &mut Vec<Token>, errors: &mut Vec<SyntaxError>) {
 
-    while let Some(c) = line.first() {
+    let mut interpolations: Vec<Interpolation> = Vec::new();
+
+    while let Some(c) = slice.first() {
         let c = *c;
 
         if is_whitespace(c) {
-            line.pop_first();
+            slice.pop_first();
             continue;
         }
 
-        let token = if is_identifier_start(c) {
-            let id_token = lex_identifier(line)?;
-
-            if let Some(kind) = KEYWORD_MAPPING.get(&id_token.text[..]) {
-                Token {
-                    kind: *kind,
-                    ..id_token
-                }
-            } else {
-                id_token
+        let next_char = slice.try_get(1).cloned();
+        if c == '/' && next_char == Some('/') {
+            match doc_comment_kind(slice) {
+                Some(kind) => result.push(lex_doc_comment(slice, kind)),
+                None => skip_line_comment(slice),
             }
+            continue;
+        }
+        if c == '/' && next_char == Some('*') {
+            if let Err(error) = skip_block_comment(slice) {
+                errors.push(error);
+            }
+            continue;
+        }
+
+        let start = *slice;
+
+        let token = if is_raw_string_start(slice) {
+            lex_raw_string(slice)
+        } else if is_identifier_start(c) {
+            lex_identifier(slice).map(keyword_token)
         } else if is_digit(c) {
-            lex_integer(line)?
+            lex_integer(slice)
         } else if is_quote(c) {
-            lex_string(line)?
+            lex_string(slice, errors)
+        } else if c == '}' && interpolations.last().map(|i| i.depth) == Some(0) {
+            let quote = interpolations.pop().unwrap().quote;
+            let token = lex_string_continuation(slice, quote, errors);
+            if let Ok(Token { kind: TokenKind::StringMiddle, .. }) = token {
+                interpolations.push(Interpolation { quote, depth: 0 });
+            }
+            token
         } else {
+            lex_symbol(slice)
+        };
 
-            let startpos = TextPosition { index: line.position() };
+        match token {
+            Ok(token) => {
+                match token.kind {
+                    TokenKind::StringStart => {
+                        interpolations.push(Interpolation {
+                            quote: token.start,
+                            depth: 0,
+                        });
+                    }
+                    TokenKind::LBrace => {
+                        if let Some(interpolation) = interpolations.last_mut() {
+                            interpolation.depth += 1;
+                        }
+                    }
+                    TokenKind::RBrace => {
+                        if let Some(interpolation) = interpolations.last_mut() {
+                            interpolation.depth -= 1;
+                        }
+                    }
+                    _ => {}
+                }
+                result.push(token);
+            }
+            Err(error) => {
+                errors.push(error);
+                result.push(error_token(start, slice));
+            }
+        }
+    }
 
-            let next_char = line.try_get(1).map(|c| *c);
-            let mut op_token = |str: &str, kind| {
+    // the input ended within interpolations, the innermost string first
+    while let Some(interpolation) = interpolations.pop() {
+        errors.push(unterminated_string(interpolation.quote, slice.position()));
+    }
 
-                line.pop_first();
+    // placed directly behind the last token, so errors about missing input
+    // point at the line where the input stopped
+    let end = result.last().map(|t| t.end).unwrap_or(slice.position());
+    // strings and comments are only unterminated when the input ends in them
+    let unterminated = errors.iter().any(|e| {
+        matches!(
+            e.code(),
+            ErrorCode::UnterminatedString |
+            ErrorCode::UnterminatedRawString |
+            ErrorCode::UnterminatedBlockComment
+        )
+    });
+    result.push(Token {
+        text: String::new(),
+        start: end,
+        end,
+        kind: TokenKind::EndOfInput,
+        value: None,
+        unterminated,
+    });
+}
 
-                if str.len() == 2 {
-                    line.pop_first(); // consume last char
-                }
+/// Skips a `//` comment up to the end of the line.
+fn skip_line_comment(slice: &mut LexSlice) {
+    while let Some(c) = slice.first() {
+        if *c == '\n' {
+            break;
+        }
+        slice.pop_first();
+    }
+}
 
-                let endpos = TextPosition { index: line.position() };
+/// Returns the kind of token for a `///` or `//!` comment, `None` for a
+/// plain `//` comment. Like `//`, four or more slashes start a plain comment.
+fn doc_comment_kind(slice: &LexSlice) -> Option<TokenKind> {
+    match (slice.try_get(2).cloned(), slice.try_get(3).cloned()) {
+        (Some('/'), Some('/')) => None,
+        (Some('/'), _) => Some(TokenKind::DocComment),
+        (Some('!'), _) => Some(TokenKind::InnerDocComment),
+        _ => None,
+    }
+}
 
-                Token {
-                    start: startpos,
-                    end: endpos,
-                    text: str.to_string(),
-                    kind: kind,
-                }
-            };
+/// Lexes a doc comment up to the end of the line.
+fn lex_doc_comment(slice: &mut LexSlice, kind: TokenKind) -> Token {
 
-            match (c, next_char) {
-                ('+', Some('+')) => op_token("++", TokenKind::PlusPlus),
-                ('+', Some('=')) => op_token("+=", TokenKind::PlusEqual),
-                ('-', Some('-')) => op_token("--", TokenKind::MinusMinus),
-                ('-', Some('=')) => op_token("-=", TokenKind::MinusEqual),
-                ('*', Some('=')) => op_token("*=", TokenKind::AsteriskEqual),
-                ('+', _) => op_token("+", TokenKind::Plus),
-                ('-', _) => op_token("-", TokenKind::Minus),
-                ('*', _) => op_token("*", TokenKind::Asterisk),
-                ('(', _) => op_token("(", TokenKind::LParen),
-                (')', _) => op_token("(", TokenKind::RParen),
-                _ => {
-                    return SyntaxError::at_pos(startpos.index, format!("Unexpected symbol '{}'", c))
-                }
+    let start = *slice;
+    for _ in 0..3 {
+        slice.pop_first();
+    }
+
+    let mut value = String::new();
+    while let Some(&c) = slice.first() {
+        if c == '\n' || (c == '\r' && slice.try_get(1) == Some(&'\n')) {
+            break;
+        }
+        value.push(c);
+        slice.pop_first();
+    }
+
+    Token {
+        text: consumed_text(start, slice),
+        start: start.position(),
+        end: slice.position(),
+        kind,
+        value: Some(value),
+        unterminated: false,
+    }
+}
+
+/// Skips a `/* ... */` comment, which may span several lines and contain
+/// nested block comments.
+fn skip_block_comment(slice: &mut LexSlice) -> SyntaxResult<()> {
+
+    let startpos = slice.position();
+    slice.pop_first();
+    slice.pop_first();
+    let opener_end = slice.position();
+
+    let mut depth = 1;
+    while depth > 0 {
+        match (slice.first().cloned(), slice.try_get(1).cloned()) {
+            (None, _) => {
+                let message =
+                    "Unterminated block comment, expected '*/' to close the '/*' opened here"
+                        .to_owned();
+                let error =
+                    SyntaxError::new(ErrorCode::UnterminatedBlockComment, startpos, opener_end, message);
+                return Err(unclosed_at_end(error, slice.position(), "comment"));
             }
-        };
+            (Some('/'), Some('*')) => {
+                slice.pop_first();
+                slice.pop_first();
+                depth += 1;
+            }
+            (Some('*'), Some('/')) => {
+                slice.pop_first();
+                slice.pop_first();
+                depth -= 1;
+            }
+            _ => {
+                slice.pop_first();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn keyword_token(id_token: Token) -> Token {
+    if let Some(kind) = KEYWORD_MAPPING.get(&id_token.text[..]) {
+        Token {
+            kind: *kind,
+            ..id_token
+        }
+    } else {
+        id_token
+    }
+}
+
+fn lex_symbol(slice: &mut LexSlice) -> SyntaxResult<Token> {
+
+    let startpos = slice.position();
+
+    let c = *slice.first().unwrap();
+    let next_char = slice.try_get(1).copied();
+    let third_char = slice.try_get(2).copied();
+    let mut op_token = |str: &str, kind| {
 
-        result.push(token);
+        for _ in str.chars() {
+            slice.pop_first();
+        }
+
+        let endpos = slice.position();
+
+        Ok(Token {
+            start: startpos,
+            end: endpos,
+            text: str.to_string(),
+            kind: kind,
+            value: None,
+            unterminated: false,
+        })
+    };
+
+    match (c, next_char) {
+        ('*', Some('*')) if third_char == Some('=') => {
+            op_token("**=", TokenKind::AsteriskAsteriskEqual)
+        }
+        ('<', Some('<')) if third_char == Some('=') => op_token("<<=", TokenKind::LessLessEqual),
+        ('>', Some('>')) if third_char == Some('=') => {
+            op_token(">>=", TokenKind::GreaterGreaterEqual)
+        }
+        ('+', Some('+')) => op_token("++", TokenKind::PlusPlus),
+        ('+', Some('=')) => op_token("+=", TokenKind::PlusEqual),
+        ('-', Some('-')) => op_token("--", TokenKind::MinusMinus),
+        ('-', Some('=')) => op_token("-=", TokenKind::MinusEqual),
+        ('*', Some('=')) => op_token("*=", TokenKind::AsteriskEqual),
+        ('*', Some('*')) => op_token("**", TokenKind::AsteriskAsterisk),
+        ('/', Some('=')) => op_token("/=", TokenKind::SlashEqual),
+        ('%', Some('=')) => op_token("%=", TokenKind::PercentEqual),
+        ('&', Some('=')) => op_token("&=", TokenKind::AmpersandEqual),
+        ('|', Some('=')) => op_token("|=", TokenKind::PipeEqual),
+        ('^', Some('=')) => op_token("^=", TokenKind::CaretEqual),
+        ('=', Some('=')) => op_token("==", TokenKind::EqualEqual),
+        ('!', Some('=')) => op_token("!=", TokenKind::ExclamationEqual),
+        ('<', Some('=')) => op_token("<=", TokenKind::LessEqual),
+        ('<', Some('<')) => op_token("<<", TokenKind::LessLess),
+        ('>', Some('=')) => op_token(">=", TokenKind::GreaterEqual),
+        ('>', Some('>')) => op_token(">>", TokenKind::GreaterGreater),
+        ('&', Some('&')) => op_token("&&", TokenKind::AmpersandAmpersand),
+        ('|', Some('|')) => op_token("||", TokenKind::PipePipe),
+        ('+', _) => op_token("+", TokenKind::Plus),
+        ('-', _) => op_token("-", TokenKind::Minus),
+        ('*', _) => op_token("*", TokenKind::Asterisk),
+        ('/', _) => op_token("/", TokenKind::Slash),
+        ('%', _) => op_token("%", TokenKind::Percent),
+        ('<', _) => op_token("<", TokenKind::Less),
+        ('>', _) => op_token(">", TokenKind::Greater),
+        ('!', _) => op_token("!", TokenKind::Exclamation),
+        ('&', _) => op_token("&", TokenKind::Ampersand),
+        ('|', _) => op_token("|", TokenKind::Pipe),
+        ('^', _) => op_token("^", TokenKind::Caret),
+        ('~', _) => op_token("~", TokenKind::Tilde),
+        ('=', _) => op_token("=", TokenKind::Equal),
+        ('(', _) => op_token("(", TokenKind::LParen),
+        (')', _) => op_token(")", TokenKind::RParen),
+        ('{', _) => op_token("{", TokenKind::LBrace),
+        ('}', _) => op_token("}", TokenKind::RBrace),
+        ('[', _) => op_token("[", TokenKind::LBracket),
+        (']', _) => op_token("]", TokenKind::RBracket),
+        (',', _) => op_token(",", TokenKind::Comma),
+        ('.', _) => op_token(".", TokenKind::Dot),
+        (';', _) => op_token(";", TokenKind::Semicolon),
+        _ => {
+            // the error covers the symbol, like the error token replacing it
+            let endpos = TextPosition {
+                index: startpos.index + c.len_utf8(),
+                column: startpos.column + 1,
+                ..startpos
+            };
+            let message = format!("Unexpected symbol '{}'", c);
+            SyntaxError::at_range(ErrorCode::UnexpectedCharacter, startpos, endpos, message)
+        }
+    }
+}
+
+/// Creates a `TokenKind::Error` token covering everything a failed lexer
+/// function consumed after `start`, but at least one character.
+fn error_token(start: LexSlice, slice: &mut LexSlice) -> Token {
+
+    if slice.position() == start.position() {
+        slice.pop_first();
     }
 
-    return Ok(());
+    Token {
+        text: consumed_text(start, slice),
+        start: start.position(),
+        end: slice.position(),
+        kind: TokenKind::Error,
+        value: None,
+        unterminated: false,
+    }
+}
+
+/// Returns the source text `slice` has been advanced over since `start`.
+fn consumed_text(start: LexSlice, slice: &LexSlice) -> String {
+    let length = slice.chars.position() - start.chars.position();
+    start.chars.get(..length).to_string()
 }
 
-fn lex_identifier(line: &mut LexSlice) -> SyntaxResult<Token> {
+fn lex_identifier(slice: &mut LexSlice) -> SyntaxResult<Token> {
 
-    let startpos = TextPosition { index: line.position() };
+    let startpos = slice.position();
 
     let mut s = String::new();
-    s.push(consume_char(line, is_identifier_start)?);
-    consume_while(line, is_identifier_body, &mut s);
+    s.push(consume_char(slice, is_identifier_start)?);
+    consume_while(slice, is_identifier_body, &mut s);
 
-    let endpos = TextPosition { index: line.position() };
+    let endpos = slice.position();
 
     Ok(Token {
         text: s,
         start: startpos,
         end: endpos,
         kind: TokenKind::Identifier,
+        value: None,
+        unterminated: false,
     })
 }
 
-fn lex_integer(line: &mut LexSlice) -> SyntaxResult<Token> {
+fn lex_integer(slice: &mut LexSlice) -> SyntaxResult<Token> {
 
-    let startpos = TextPosition { index: line.position() };
+    let startpos = slice.position();
 
     let mut s = String::new();
-    consume_while(line, is_digit, &mut s);
+    consume_while(slice, is_digit, &mut s);
 
-    let endpos = TextPosition { index: line.position() };
-    dump!(endpos, line.to_string());
+    let endpos = slice.position();
 
     Ok(Token {
         text: s,
         start: startpos,
         end: endpos,
         kind: TokenKind::Integer,
+        value: None,
+        unterminated: false,
     })
 }
 
-fn lex_string(line: &mut LexSlice) -> SyntaxResult<Token> {
+/// Lexes a string literal, which may span several lines. Invalid escape
+/// sequences are reported to `errors` and turn a literal without
+/// interpolations into a `TokenKind::Error` token. The pieces of an
+/// interpolated string keep their kind so its interpolations are still parsed.
+fn lex_string(slice: &mut LexSlice, errors: &mut Vec<SyntaxError>) -> SyntaxResult<Token> {
+    let start = *slice;
+    consume_char(slice, is_quote)?;
+
+    let error_count = errors.len();
+    let mut token = lex_string_contents(slice, start, start.position(), errors)?;
+    if token.kind == TokenKind::String && errors.len() > error_count {
+        token.kind = TokenKind::Error;
+        token.value = None;
+    }
+    Ok(token)
+}
 
-    let startpos = TextPosition { index: line.position() };
+/// Lexes the rest of an interpolated string literal after an interpolation,
+/// starting with the `}` which closes the interpolation. `quote` is the
+/// position where the string literal started.
+fn lex_string_continuation(
+    slice: &mut LexSlice,
+    quote: TextPosition,
+    errors: &mut Vec<SyntaxError>,
+) -> SyntaxResult<Token> {
+    let start = *slice;
+    slice.pop_first();
+    lex_string_contents(slice, start, quote, errors)
+}
 
-    let mut s = String::new();
-    s.push(consume_char(line, is_quote)?);
-    consume_while(line, is_string_body, &mut s);
-    s.push(consume_char(line, is_quote)?);
+/// Lexes the contents of a string literal, which may span several lines, up
+/// to the closing quote or the next `${`. `start` is where the token started,
+/// either at the opening quote or at the `}` of an interpolation. Invalid
+/// escape sequences are reported to `errors` and left out of the value.
+fn lex_string_contents(
+    slice: &mut LexSlice,
+    start: LexSlice,
+    quote: TextPosition,
+    errors: &mut Vec<SyntaxError>,
+) -> SyntaxResult<Token> {
+
+    let continued = start.first() == Some(&'}');
+    let mut value = String::new();
+
+    let kind = loop {
+        match slice.first().cloned() {
+            None => return Err(unterminated_string(quote, slice.position())),
+            Some('"') => {
+                slice.pop_first();
+                break if continued { TokenKind::StringEnd } else { TokenKind::String };
+            }
+            Some('$') if slice.try_get(1) == Some(&'{') => {
+                slice.pop_first();
+                slice.pop_first();
+                break if continued { TokenKind::StringMiddle } else { TokenKind::StringStart };
+            }
+            Some('\\') => {
+                match lex_escape(slice) {
+                    Ok(c) => value.push(c),
+                    Err(error) => errors.push(error),
+                }
+            }
+            Some(c) => {
+                slice.pop_first();
+                value.push(c);
+            }
+        }
+    };
+
+    Ok(Token {
+        text: consumed_text(start, slice),
+        start: start.position(),
+        end: slice.position(),
+        kind,
+        value: Some(value),
+        unterminated: false,
+    })
+}
+
+/// Reports the string literal opened by the quote at `quote`, the input
+/// ended at `end` before it was closed.
+fn unterminated_string(quote: TextPosition, end: TextPosition) -> SyntaxError {
+    // the quote is a single byte on a single line
+    let quote_end = TextPosition {
+        index: quote.index + 1,
+        column: quote.column + 1,
+        ..quote
+    };
+    let message = "Unterminated string literal".to_owned();
+    let error = SyntaxError::new(ErrorCode::UnterminatedString, quote, quote_end, message);
+    unclosed_at_end(error, end, "string")
+}
+
+/// Points out the end of the input at `end` in an error about an unclosed
+/// `what`.
+fn unclosed_at_end(error: SyntaxError, end: TextPosition, what: &str) -> SyntaxError {
+    let message = format!("the input ends before the {} is closed", what);
+    error.with_related(end, end, message)
+}
+
+/// Lexes a raw string literal like `r"..."` or `r#"..."#`. Its contents are
+/// taken as they are, without decoding escape sequences, and end at the first
+/// quote followed by as many `#` as the literal started with.
+fn lex_raw_string(slice: &mut LexSlice) -> SyntaxResult<Token> {
 
-    let endpos = TextPosition { index: line.position() };
+    let start = *slice;
+    slice.pop_first();
+
+    let mut hashes = 0;
+    while slice.first() == Some(&'#') {
+        slice.pop_first();
+        hashes += 1;
+    }
+    consume_char(slice, is_quote)?;
+    let opener_end = slice.position();
+
+    let mut value = String::new();
+
+    loop {
+        match slice.first().cloned() {
+            None => {
+                let message = format!(
+                    "Unterminated raw string literal, expected '\"{0}' to close the 'r{0}\"' opened here",
+                    "#".repeat(hashes)
+                );
+                let start = start.position();
+                let error = SyntaxError::new(ErrorCode::UnterminatedRawString, start, opener_end, message);
+                return Err(unclosed_at_end(error, slice.position(), "string"));
+            }
+            Some('"') if (1..=hashes).all(|i| slice.try_get(i) == Some(&'#')) => {
+                for _ in 0..=hashes {
+                    slice.pop_first();
+                }
+                break;
+            }
+            Some(c) => {
+                slice.pop_first();
+                value.push(c);
+            }
+        }
+    }
 
     Ok(Token {
-        text: s,
-        start: startpos,
-        end: endpos,
+        text: consumed_text(start, slice),
+        start: start.position(),
+        end: slice.position(),
         kind: TokenKind::String,
+        value: Some(value),
+        unterminated: false,
     })
 }
 
+/// Lexes an escape sequence starting with a backslash and returns the
+/// character it stands for.
+fn lex_escape(slice: &mut LexSlice) -> SyntaxResult<char> {
+
+    let startpos = slice.position();
+    slice.pop_first();
+
+    let c = match slice.pop_first() {
+        Some(c) => *c,
+        // the caller reports the unterminated string
+        None => return Ok('\\'),
+    };
+
+    match c {
+        'n' => Ok('\n'),
+        't' => Ok('\t'),
+        '\\' => Ok('\\'),
+        '"' => Ok('"'),
+        '$' => Ok('$'),
+        'u' => lex_unicode_escape(slice, startpos),
+        _ => {
+            let message = format!("Unknown escape sequence '\\{}'", c.escape_default());
+            SyntaxError::at_range(ErrorCode::InvalidEscape, startpos, slice.position(), message)
+        }
+    }
+}
+
+/// Lexes the `{...}` part of a `\u{...}` escape sequence starting at
+/// `startpos`.
+fn lex_unicode_escape(slice: &mut LexSlice, startpos: TextPosition) -> SyntaxResult<char> {
+
+    let invalid = |slice: &LexSlice| {
+        let message = "Invalid unicode escape, expected '\\u{' followed by 1 to 6 hex digits and '}'"
+            .to_owned();
+        SyntaxError::at_range(ErrorCode::InvalidEscape, startpos, slice.position(), message)
+    };
+
+    if slice.first() != Some(&'{') {
+        return invalid(slice);
+    }
+    slice.pop_first();
+
+    let mut digits = String::new();
+    consume_while(slice, is_hex_digit, &mut digits);
+
+    if digits.is_empty() || digits.len() > 6 || slice.first() != Some(&'}') {
+        return invalid(slice);
+    }
+    slice.pop_first();
+
+    let code = u32::from_str_radix(&digits, 16).expect("at most 6 hex digits");
+    match char::from_u32(code) {
+        Some(c) => Ok(c),
+        None => {
+            let message = format!("Invalid unicode escape, U+{:X} is not a valid character", code);
+            SyntaxError::at_range(ErrorCode::InvalidEscape, startpos, slice.position(), message)
+        }
+    }
+}
+
 fn consume_char(slice: &mut LexSlice, predicate: fn(char) -> bool) -> SyntaxResult<char> {
 
     match slice.pop_first() {
-        None => SyntaxError::at_pos(slice.position(), "Unexpected EOF".to_owned()),
+        None => {
+            let message = "Unexpected EOF".to_owned();
+            SyntaxError::at_pos(ErrorCode::UnexpectedCharacter, slice.position(), message)
+        }
         Some(c) if predicate(*c) => Ok(*c),
-        _ => SyntaxError::at_pos(slice.position(), "Invalid symbol".to_owned()),
+        _ => {
+            let message = "Invalid symbol".to_owned();
+            SyntaxError::at_pos(ErrorCode::UnexpectedCharacter, slice.position(), message)
+        }
     }
 }
 
@@ -184,6 +709,10 @@ fn is_digit(c: char) -> bool {
     ('0' <= c && c <= '9')
 }
 
+fn is_hex_digit(c: char) -> bool {
+    c.is_ascii_hexdigit()
+}
+
 fn is_identifier_start(c: char) -> bool {
     ('a' <= c && c <= 'z') || ('A' <= c && c <= 'Z') || (c == '_')
 }
@@ -192,10 +721,20 @@ fn is_identifier_body(c: char) -> bool {
     is_identifier_start(c) || is_digit(c)
 }
 
-fn is_quote(c: char) -> bool {
-    return c == '"';
+/// Checks for an `r` followed by any number of `#` and a quote.
+fn is_raw_string_start(slice: &LexSlice) -> bool {
+
+    if slice.first() != Some(&'r') {
+        return false;
+    }
+
+    let mut index = 1;
+    while slice.try_get(index) == Some(&'#') {
+        index += 1;
+    }
+    slice.try_get(index) == Some(&'"')
 }
 
-fn is_string_body(c: char) -> bool {
-    !is_quote(c)
+fn is_quote(c: char) -> bool {
+    return c == '"';
 }
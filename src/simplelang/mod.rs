@@ -4,33 +4,96 @@ pub mod indexed_slice;
 pub mod lexer;
 pub mod parser;
 pub mod ast;
+pub mod source_map;
+pub mod diagnostics;
+pub mod semantic;
+pub mod interpreter;
+pub mod docs;
 
 use std::collections::HashMap;
+use std::fmt;
 use std::hash::Hash;
+use simplelang::source_map::FileId;
+use simplelang::diagnostics::{ErrorCode, Label, Severity};
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Token {
+    /// The source text of the token.
     pub text: String,
     pub start: TextPosition,
     pub end: TextPosition,
     pub kind: TokenKind,
+    /// The contents of a string literal with its escape sequences decoded or
+    /// the text of a doc comment after its `///` or `//!`, `None` for every
+    /// other kind of token.
+    pub value: Option<String>,
+    /// Set on the `TokenKind::EndOfInput` token if the input ended within an
+    /// unterminated string or comment, which the lexer already reported.
+    pub unterminated: bool,
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// A location in the source text.
+///
+/// `index` is the byte offset from the start of the file. `line` and `column`
+/// are zero-based, `column` counts characters from the start of the line.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
 pub struct TextPosition {
+    pub file: FileId,
     pub index: usize,
+    pub line: usize,
+    pub column: usize,
 }
 
-static UNKNOWN_POSITION: TextPosition = TextPosition { index: 0 };
+/// The source range from `start` up to, but excluding, `end`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Span {
+    pub start: TextPosition,
+    pub end: TextPosition,
+}
+
+impl Span {
+    pub fn new(start: TextPosition, end: TextPosition) -> Self {
+        Span { start, end }
+    }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+    pub fn from_token(token: &Token) -> Self {
+        Span::new(token.start, token.end)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum TokenKind {
+    /// Input the lexer could not make sense of, see the accompanying `SyntaxError`.
+    Error,
+    /// Last token of every token stream, marks where the input ended.
+    EndOfInput,
+
     Identifier,
     Integer,
     String,
+    /// Start of an interpolated string up to the first `${`, like `"a ${`.
+    StringStart,
+    /// Part of an interpolated string between two interpolations, like
+    /// `} b ${`.
+    StringMiddle,
+    /// End of an interpolated string after the last interpolation, like
+    /// `} c"`.
+    StringEnd,
+    /// `/// text`, documents the item which follows it.
+    DocComment,
+    /// `//! text`, documents the module it is placed in.
+    InnerDocComment,
 
     // keywords
     FnKeyword,
+    LetKeyword,
+    IfKeyword,
+    ElseKeyword,
+    WhileKeyword,
+    LoopKeyword,
+    BreakKeyword,
+    ContinueKeyword,
+    ReturnKeyword,
 
     // symbols
     Plus,
@@ -41,8 +104,42 @@ pub enum TokenKind {
     MinusEqual,
     Asterisk,
     AsteriskEqual,
+    AsteriskAsterisk,
+    AsteriskAsteriskEqual,
+    Slash,
+    SlashEqual,
+    Percent,
+    PercentEqual,
+    Equal,
+    EqualEqual,
+    ExclamationEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Exclamation,
+    AmpersandAmpersand,
+    PipePipe,
+    Ampersand,
+    AmpersandEqual,
+    Pipe,
+    PipeEqual,
+    Caret,
+    CaretEqual,
+    Tilde,
+    LessLess,
+    LessLessEqual,
+    GreaterGreater,
+    GreaterGreaterEqual,
     LParen,
     RParen,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Comma,
+    Dot,
+    Semicolon,
 }
 
 impl TokenKind {
@@ -52,6 +149,8 @@ impl TokenKind {
             TokenKind::Minus => Some(ast::UnaryOperator::Negate),
             TokenKind::PlusPlus => Some(ast::UnaryOperator::PreInc),
             TokenKind::MinusMinus => Some(ast::UnaryOperator::PreDec),
+            TokenKind::Exclamation => Some(ast::UnaryOperator::Not),
+            TokenKind::Tilde => Some(ast::UnaryOperator::BitNot),
             _ => None,
         }
     }
@@ -65,41 +164,197 @@ impl TokenKind {
     }
 }
 
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            TokenKind::Error => "invalid token",
+            TokenKind::EndOfInput => "end of input",
+            TokenKind::Identifier => "identifier",
+            TokenKind::Integer => "integer",
+            TokenKind::String => "string",
+            TokenKind::StringStart => "string",
+            TokenKind::StringMiddle => "'}'",
+            TokenKind::StringEnd => "'}'",
+            TokenKind::DocComment => "doc comment",
+            TokenKind::InnerDocComment => "inner doc comment",
+            TokenKind::FnKeyword => "'fn'",
+            TokenKind::LetKeyword => "'let'",
+            TokenKind::IfKeyword => "'if'",
+            TokenKind::ElseKeyword => "'else'",
+            TokenKind::WhileKeyword => "'while'",
+            TokenKind::LoopKeyword => "'loop'",
+            TokenKind::BreakKeyword => "'break'",
+            TokenKind::ContinueKeyword => "'continue'",
+            TokenKind::ReturnKeyword => "'return'",
+            TokenKind::Plus => "'+'",
+            TokenKind::PlusPlus => "'++'",
+            TokenKind::PlusEqual => "'+='",
+            TokenKind::Minus => "'-'",
+            TokenKind::MinusMinus => "'--'",
+            TokenKind::MinusEqual => "'-='",
+            TokenKind::Asterisk => "'*'",
+            TokenKind::AsteriskEqual => "'*='",
+            TokenKind::AsteriskAsterisk => "'**'",
+            TokenKind::AsteriskAsteriskEqual => "'**='",
+            TokenKind::Slash => "'/'",
+            TokenKind::SlashEqual => "'/='",
+            TokenKind::Percent => "'%'",
+            TokenKind::PercentEqual => "'%='",
+            TokenKind::Equal => "'='",
+            TokenKind::EqualEqual => "'=='",
+            TokenKind::ExclamationEqual => "'!='",
+            TokenKind::Less => "'<'",
+            TokenKind::LessEqual => "'<='",
+            TokenKind::Greater => "'>'",
+            TokenKind::GreaterEqual => "'>='",
+            TokenKind::Exclamation => "'!'",
+            TokenKind::AmpersandAmpersand => "'&&'",
+            TokenKind::PipePipe => "'||'",
+            TokenKind::Ampersand => "'&'",
+            TokenKind::AmpersandEqual => "'&='",
+            TokenKind::Pipe => "'|'",
+            TokenKind::PipeEqual => "'|='",
+            TokenKind::Caret => "'^'",
+            TokenKind::CaretEqual => "'^='",
+            TokenKind::Tilde => "'~'",
+            TokenKind::LessLess => "'<<'",
+            TokenKind::LessLessEqual => "'<<='",
+            TokenKind::GreaterGreater => "'>>'",
+            TokenKind::GreaterGreaterEqual => "'>>='",
+            TokenKind::LParen => "'('",
+            TokenKind::RParen => "')'",
+            TokenKind::LBrace => "'{'",
+            TokenKind::RBrace => "'}'",
+            TokenKind::LBracket => "'['",
+            TokenKind::RBracket => "']'",
+            TokenKind::Comma => "','",
+            TokenKind::Dot => "'.'",
+            TokenKind::Semicolon => "';'",
+        };
+        f.write_str(name)
+    }
+}
+
 pub type SyntaxResult<T> = Result<T, SyntaxError>;
 
 #[derive(Debug)]
 pub struct SyntaxError {
+    /// Stable identifier of the kind of error, see `diagnostics::to_json`.
+    code: ErrorCode,
+    /// `Severity::Warning` for problems which do not stop the program from
+    /// being run.
+    severity: Severity,
     start: TextPosition,
     end: TextPosition,
     message: String,
+    /// Token kinds which would have been valid, empty unless the parser
+    /// reported an unexpected token.
+    expected: Box<[TokenKind]>,
+    /// Another source range which explains the error, like the end of the
+    /// input for an unterminated literal. Boxed to keep errors small, the
+    /// parser passes them around by value.
+    related: Option<Box<Label>>,
 }
 
 impl SyntaxError {
-    fn at_pos<T>(position: usize, message: String) -> SyntaxResult<T> {
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn start(&self) -> TextPosition {
+        self.start
+    }
+
+    pub fn end(&self) -> TextPosition {
+        self.end
+    }
 
-        let pos = TextPosition { index: position };
+    pub fn message(&self) -> &str {
+        &self.message
+    }
 
-        Err(SyntaxError {
-            start: pos,
-            end: pos,
-            message: message,
-        })
+    pub fn expected(&self) -> &[TokenKind] {
+        &self.expected
     }
 
-    fn at_range<T>(start: TextPosition, end: TextPosition, message: String) -> SyntaxResult<T> {
-        Err(SyntaxError {
-            start: start,
-            end: end,
-            message: message,
-        })
+    pub fn related(&self) -> Option<&Label> {
+        self.related.as_deref()
     }
 
-    fn from_token<T>(token: &Token, message: String) -> SyntaxResult<T> {
-        Err(SyntaxError {
-            start: token.start,
-            end: token.end,
+    fn new(code: ErrorCode, start: TextPosition, end: TextPosition, message: String) -> Self {
+        SyntaxError {
+            code,
+            severity: Severity::Error,
+            start,
+            end,
             message,
-        })
+            expected: Box::new([]),
+            related: None,
+        }
+    }
+
+    fn into_warning(self) -> SyntaxError {
+        SyntaxError {
+            severity: Severity::Warning,
+            ..self
+        }
+    }
+
+    fn with_related(self, start: TextPosition, end: TextPosition, message: String) -> Self {
+        SyntaxError {
+            related: Some(Box::new(Label {
+                start,
+                end,
+                message,
+            })),
+            ..self
+        }
+    }
+
+    /// Creates an error for the token `found` when one of the `expected`
+    /// token kinds was required.
+    fn unexpected(found: &Token, expected: &[TokenKind]) -> SyntaxError {
+
+        let mut expected = expected.to_vec();
+        expected.sort();
+        expected.dedup();
+
+        let found_text = match found.kind {
+            TokenKind::EndOfInput => found.kind.to_string(),
+            _ => format!("'{}'", found.text),
+        };
+
+        let expected_text: Vec<_> = expected.iter().map(|k| k.to_string()).collect();
+        let message = match expected_text.len() {
+            1 => format!("Expected {} but found {}", expected_text[0], found_text),
+            _ => format!(
+                "Expected one of: {} but found {}",
+                expected_text.join(", "),
+                found_text
+            ),
+        };
+
+        SyntaxError {
+            expected: expected.into_boxed_slice(),
+            ..SyntaxError::new(ErrorCode::UnexpectedToken, found.start, found.end, message)
+        }
+    }
+
+    fn at_pos<T>(code: ErrorCode, position: TextPosition, message: String) -> SyntaxResult<T> {
+        Err(SyntaxError::new(code, position, position, message))
+    }
+
+    fn at_range<T>(
+        code: ErrorCode,
+        start: TextPosition,
+        end: TextPosition,
+        message: String,
+    ) -> SyntaxResult<T> {
+        Err(SyntaxError::new(code, start, end, message))
     }
 }
 
@@ -1,22 +1,74 @@
 use simplelang::*;
 use simplelang::ast::*;
+use simplelang::diagnostics::ErrorCode;
 use std::collections::HashMap;
 
+/// Precedence of `**`, the only binary operator which binds tighter than the
+/// prefix operators `-`, `+`, `!` and `~`. `++` and `--` bind tighter still,
+/// they apply to a variable and not to a power.
+const POW_PRECEDENCE: u32 = 11;
+
 lazy_static! {
 	static ref OPERATORS: HashMap<TokenKind, OperatorInfo> = {
 		let mut map = HashMap::new();
 
-        map.insert_key(OperatorInfo {
-            token_kind: TokenKind::Asterisk,
-            operator: BinaryOperator::Mul,
-            precedence: 6,
-        });
+        // from the loosest to the tightest binding operators, prefix operators
+        // bind tighter than all of them but `**`, so `-2 ** 2` is `-(2 ** 2)`
+        // while `++x ** 2` is `(++x) ** 2`
+        let operators = [
+            (TokenKind::PipePipe, BinaryOperator::Or, 2, Associativity::Left),
+            (TokenKind::AmpersandAmpersand, BinaryOperator::And, 3, Associativity::Left),
+            (TokenKind::EqualEqual, BinaryOperator::Eq, 4, Associativity::NonAssociative),
+            (TokenKind::ExclamationEqual, BinaryOperator::NotEq, 4, Associativity::NonAssociative),
+            (TokenKind::Less, BinaryOperator::Less, 4, Associativity::NonAssociative),
+            (TokenKind::LessEqual, BinaryOperator::LessEq, 4, Associativity::NonAssociative),
+            (TokenKind::Greater, BinaryOperator::Greater, 4, Associativity::NonAssociative),
+            (TokenKind::GreaterEqual, BinaryOperator::GreaterEq, 4, Associativity::NonAssociative),
+            (TokenKind::Pipe, BinaryOperator::BitOr, 5, Associativity::Left),
+            (TokenKind::Caret, BinaryOperator::BitXor, 6, Associativity::Left),
+            (TokenKind::Ampersand, BinaryOperator::BitAnd, 7, Associativity::Left),
+            (TokenKind::LessLess, BinaryOperator::Shl, 8, Associativity::Left),
+            (TokenKind::GreaterGreater, BinaryOperator::Shr, 8, Associativity::Left),
+            (TokenKind::Plus, BinaryOperator::Add, 9, Associativity::Left),
+            (TokenKind::Minus, BinaryOperator::Sub, 9, Associativity::Left),
+            (TokenKind::Asterisk, BinaryOperator::Mul, 10, Associativity::Left),
+            (TokenKind::Slash, BinaryOperator::Div, 10, Associativity::Left),
+            (TokenKind::Percent, BinaryOperator::Rem, 10, Associativity::Left),
+            (TokenKind::AsteriskAsterisk, BinaryOperator::Pow, POW_PRECEDENCE, Associativity::Right),
+        ];
+
+        for &(token_kind, operator, precedence, associativity) in operators.iter() {
+            map.insert_key(OperatorInfo {
+                token_kind,
+                operation: Operation::Binary(operator),
+                precedence,
+                associativity,
+            });
+        }
 
-        map.insert_key(OperatorInfo {
-            token_kind: TokenKind::Plus,
-            operator: BinaryOperator::Add,
-            precedence: 5,
-        });
+        let assignments = [
+            (TokenKind::Equal, None),
+            (TokenKind::PlusEqual, Some(BinaryOperator::Add)),
+            (TokenKind::MinusEqual, Some(BinaryOperator::Sub)),
+            (TokenKind::AsteriskEqual, Some(BinaryOperator::Mul)),
+            (TokenKind::AsteriskAsteriskEqual, Some(BinaryOperator::Pow)),
+            (TokenKind::SlashEqual, Some(BinaryOperator::Div)),
+            (TokenKind::PercentEqual, Some(BinaryOperator::Rem)),
+            (TokenKind::AmpersandEqual, Some(BinaryOperator::BitAnd)),
+            (TokenKind::PipeEqual, Some(BinaryOperator::BitOr)),
+            (TokenKind::CaretEqual, Some(BinaryOperator::BitXor)),
+            (TokenKind::LessLessEqual, Some(BinaryOperator::Shl)),
+            (TokenKind::GreaterGreaterEqual, Some(BinaryOperator::Shr)),
+        ];
+
+        for &(token_kind, operator) in assignments.iter() {
+            map.insert_key(OperatorInfo {
+                token_kind,
+                operation: Operation::Assign(operator),
+                precedence: 1,
+                associativity: Associativity::Right,
+            });
+        }
 
 		map
 	};
@@ -24,36 +76,552 @@ lazy_static! {
 
 type ParseResult<'a, T> = SyntaxResult<(&'a [Token], T)>;
 
-pub fn expression<'a>(tokens: &'a [Token]) -> ParseResult<'a, ExpressionNode> {
-    let (tokens, lhs) = primary_expression(tokens)?;
-    binary_rhs_expression(tokens, lhs, 0)
+/// Tokens which can start an operand, including its prefix operators.
+/// `TokenKind::StringStart` is left out since it is named like a string.
+static OPERAND_START: &[TokenKind] = &[
+    TokenKind::Identifier,
+    TokenKind::Integer,
+    TokenKind::String,
+    TokenKind::IfKeyword,
+    TokenKind::LParen,
+    TokenKind::LBrace,
+    TokenKind::Plus,
+    TokenKind::PlusPlus,
+    TokenKind::Minus,
+    TokenKind::MinusMinus,
+    TokenKind::Exclamation,
+    TokenKind::Tilde,
+];
+
+/// Tokens which continue an operand: calls, indexing, field access and the
+/// unary postfix operators.
+static POSTFIX_OPERATORS: &[TokenKind] = &[
+    TokenKind::LParen,
+    TokenKind::LBracket,
+    TokenKind::Dot,
+    TokenKind::PlusPlus,
+    TokenKind::MinusMinus,
+];
+
+/// Tokens at which the parser resumes after a syntax error.
+static SYNCHRONIZATION_POINTS: &[TokenKind] = &[
+    TokenKind::EndOfInput,
+    TokenKind::RParen,
+    TokenKind::LBrace,
+    TokenKind::RBrace,
+    TokenKind::RBracket,
+    TokenKind::Semicolon,
+    TokenKind::FnKeyword,
+    TokenKind::StringMiddle,
+    TokenKind::StringEnd,
+];
+
+/// Tokens which end an interpolation `${...}` within a string.
+static INTERPOLATION_END: &[TokenKind] = &[
+    TokenKind::StringMiddle,
+    TokenKind::StringEnd,
+];
+
+/// Tokens which can start a statement, besides those in `OPERAND_START`.
+static STATEMENT_START: &[TokenKind] = &[
+    TokenKind::LetKeyword,
+    TokenKind::IfKeyword,
+    TokenKind::WhileKeyword,
+    TokenKind::LoopKeyword,
+    TokenKind::BreakKeyword,
+    TokenKind::ContinueKeyword,
+    TokenKind::ReturnKeyword,
+    TokenKind::Semicolon,
+];
+
+/// Tokens which can start an item or end the program.
+static ITEM_START: &[TokenKind] = &[TokenKind::FnKeyword, TokenKind::EndOfInput];
+
+/// Parses all tokens as a program, a module of items. A malformed item is
+/// skipped up to the start of the next one and every error is returned.
+pub fn program(tokens: &[Token]) -> (Module, Vec<SyntaxError>) {
+
+    let mut errors = Vec::new();
+    let mut items = Vec::new();
+    let (mut tokens, docs) = doc_comments(tokens, TokenKind::InnerDocComment);
+
+    loop {
+        let t = pop_first(tokens).0;
+        match t.kind {
+            TokenKind::EndOfInput => break,
+            // documents nothing, like doc comments at the end of the file
+            TokenKind::DocComment if !at_function(tokens) => {
+                report(&mut errors, misplaced_doc_comment(t));
+                tokens = doc_comments(tokens, TokenKind::DocComment).0;
+            }
+            TokenKind::DocComment |
+            TokenKind::FnKeyword => {
+                match function_decl(tokens, &mut errors) {
+                    Ok((ts, function)) => {
+                        items.push(Item::Function(function));
+                        tokens = ts;
+                    }
+                    Err(error) => {
+                        report(&mut errors, error);
+                        tokens = skip_to_item(tokens);
+                    }
+                }
+            }
+            TokenKind::InnerDocComment => {
+                report(&mut errors, misplaced_doc_comment(t));
+                tokens = pop_first(tokens).1;
+            }
+            // the lexer already reported the error
+            TokenKind::Error => tokens = skip_to_item(tokens),
+            _ => {
+                report(&mut errors, SyntaxError::unexpected(t, ITEM_START));
+                tokens = skip_to_item(tokens);
+            }
+        }
+    }
+
+    (Module { docs, items }, errors)
+}
+
+/// Parses `fn name(a, b) { body }` and the doc comments in front of it.
+pub fn function_decl<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, FunctionDecl> {
+
+    let (ts, docs) = doc_comments(tokens, TokenKind::DocComment);
+    let (ts, fn_token) = expect(ts, TokenKind::FnKeyword)?;
+    let (ts, name) = expect(ts, TokenKind::Identifier)?;
+    let (ts, parameters) = parameter_list(ts)?;
+    let (ts, body) = block(ts, errors)?;
+
+    Ok((
+        ts,
+        FunctionDecl {
+            docs,
+            name: name.text.clone(),
+            parameters,
+            span: Span::new(fn_token.start, body.span.end),
+            body,
+        },
+    ))
+}
+
+/// Collects the text of consecutive doc comments of the given kind, one
+/// entry per line.
+fn doc_comments(tokens: &[Token], kind: TokenKind) -> (&[Token], Vec<String>) {
+
+    let mut tokens = tokens;
+    let mut docs = Vec::new();
+
+    while let Some((t, ts)) = tokens.split_first() {
+        if t.kind != kind {
+            break;
+        }
+        docs.push(t.value.clone().expect("doc comments carry their text"));
+        tokens = ts;
+    }
+
+    (tokens, docs)
+}
+
+/// Whether the tokens start with a function, possibly after its doc
+/// comments.
+fn at_function(tokens: &[Token]) -> bool {
+    let tokens = doc_comments(tokens, TokenKind::DocComment).0;
+    pop_first(tokens).0.kind == TokenKind::FnKeyword
+}
+
+fn misplaced_doc_comment(token: &Token) -> SyntaxError {
+    let message = match token.kind {
+        TokenKind::InnerDocComment => "Inner doc comments are only allowed at the start of a file",
+        _ => "Doc comments are only allowed in front of functions",
+    };
+    SyntaxError::new(ErrorCode::MisplacedDocComment, token.start, token.end, message.to_string())
+}
+
+/// Parses `(a, b)`, a trailing comma is allowed.
+fn parameter_list(tokens: &[Token]) -> ParseResult<'_, Vec<Parameter>> {
+
+    let (mut tokens, _) = expect(tokens, TokenKind::LParen)?;
+    let mut parameters = Vec::new();
+
+    loop {
+        let (t, ts) = pop_first(tokens);
+        match t.kind {
+            TokenKind::RParen => return Ok((ts, parameters)),
+            TokenKind::Identifier => {
+                parameters.push(Parameter {
+                    name: t.text.clone(),
+                    span: Span::from_token(t),
+                });
+            }
+            _ => {
+                let expected = [TokenKind::Identifier, TokenKind::RParen];
+                return Err(SyntaxError::unexpected(t, &expected));
+            }
+        }
+
+        let (t, ts) = pop_first(ts);
+        match t.kind {
+            TokenKind::RParen => return Ok((ts, parameters)),
+            TokenKind::Comma => tokens = ts,
+            _ => {
+                let expected = [TokenKind::Comma, TokenKind::RParen];
+                return Err(SyntaxError::unexpected(t, &expected));
+            }
+        }
+    }
+}
+
+/// Parses `{ statements value }`. Empty statements, a lone `;`, are skipped.
+/// A malformed statement is reported and skipped, and a block which is not
+/// closed ends at the next item.
+pub fn block<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, Block> {
+
+    let block_tokens = tokens;
+    let (mut tokens, _) = expect(tokens, TokenKind::LBrace)?;
+    let mut statements = Vec::new();
+    let mut value = None;
+
+    loop {
+        let (t, ts) = pop_first(tokens);
+        match t.kind {
+            TokenKind::RBrace => {
+                let span = consumed_span(block_tokens, ts);
+                return Ok((ts, Block { statements, value, span }));
+            }
+            // without a closing brace the block ends at the next function
+            _ if t.kind == TokenKind::EndOfInput || at_function(tokens) => {
+                if !reported_by_lexer(t) {
+                    let mut expected = statement_start();
+                    expected.push(TokenKind::RBrace);
+                    report(errors, SyntaxError::unexpected(t, &expected));
+                }
+
+                let span = consumed_span(block_tokens, tokens);
+                return Ok((tokens, Block { statements, value, span }));
+            }
+            // most likely meant as a plain comment on a statement
+            TokenKind::DocComment |
+            TokenKind::InnerDocComment => {
+                report(errors, misplaced_doc_comment(t).into_warning());
+                tokens = ts;
+            }
+            TokenKind::Semicolon => tokens = ts,
+            _ => {
+                match statement(tokens, errors) {
+                    Ok((ts, BlockPart::Statement(statement))) => {
+                        statements.push(statement);
+                        tokens = ts;
+                    }
+                    Ok((ts, BlockPart::Value(exp))) => {
+                        value = Some(Box::new(exp));
+                        tokens = ts;
+                    }
+                    Err(error) => {
+                        tokens = skip_statement(tokens_from(tokens, error.start));
+                        report(errors, error);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A statement, or the expression at the end of a block.
+enum BlockPart {
+    Statement(StatementNode),
+    Value(ExpressionNode),
+}
+
+/// Parses a single statement. Expressions which end in a block, like `if`
+/// and `{ .. }`, and the statements `while` and `loop` are not followed by
+/// a `;`, all others are. An expression directly before the closing brace
+/// of the block is not a statement but the value of the block.
+fn statement<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, BlockPart> {
+
+    let (t, mut ts) = pop_first(tokens);
+
+    let kind = match t.kind {
+        TokenKind::LetKeyword => {
+            assign!{ (ts, let name) = expect(ts, TokenKind::Identifier)? };
+
+            let mut value = None;
+            match pop_first(ts) {
+                (&Token { kind: TokenKind::Equal, .. }, rest) => {
+                    assign!{ (ts, let exp) = expression(rest, errors)? };
+                    ts = end_of_statement(ts, &[])?;
+                    value = Some(exp);
+                }
+                (&Token { kind: TokenKind::Semicolon, .. }, rest) => ts = rest,
+                (t, _) => {
+                    let expected = [TokenKind::Equal, TokenKind::Semicolon];
+                    return Err(SyntaxError::unexpected(t, &expected));
+                }
+            }
+
+            Statement::Let(name.text.clone(), value)
+        }
+        TokenKind::WhileKeyword => {
+            assign!{ (ts, let condition) = expression(ts, errors)? };
+            assign!{ (ts, let body) = block(ts, errors)? };
+            Statement::While(condition, body)
+        }
+        TokenKind::LoopKeyword => {
+            assign!{ (ts, let body) = block(ts, errors)? };
+            Statement::Loop(body)
+        }
+        TokenKind::BreakKeyword => {
+            ts = expect(ts, TokenKind::Semicolon)?.0;
+            Statement::Break
+        }
+        TokenKind::ContinueKeyword => {
+            ts = expect(ts, TokenKind::Semicolon)?.0;
+            Statement::Continue
+        }
+        TokenKind::ReturnKeyword => {
+            match pop_first(ts) {
+                (&Token { kind: TokenKind::Semicolon, .. }, rest) => {
+                    ts = rest;
+                    Statement::Return(None)
+                }
+                _ => {
+                    assign!{ (ts, let exp) = expression(ts, errors)? };
+                    ts = end_of_statement(ts, &[])?;
+                    Statement::Return(Some(exp))
+                }
+            }
+        }
+        TokenKind::IfKeyword |
+        TokenKind::LBrace => {
+            // nothing continues the expression, `if c {} - 1` are two statements
+            assign!{ (ts, let exp) = block_like_expression(tokens, errors)? };
+            match pop_first(ts) {
+                (&Token { kind: TokenKind::RBrace, .. }, _) => {
+                    return Ok((ts, BlockPart::Value(exp)));
+                }
+                (&Token { kind: TokenKind::Semicolon, .. }, rest) => ts = rest,
+                _ => {}
+            }
+            Statement::Expression(exp)
+        }
+        _ => {
+            assign!{ (ts, let exp) = expression(tokens, errors)? };
+            if let (&Token { kind: TokenKind::RBrace, .. }, _) = pop_first(ts) {
+                return Ok((ts, BlockPart::Value(exp)));
+            }
+            ts = end_of_statement(ts, &[TokenKind::RBrace])?;
+            Statement::Expression(exp)
+        }
+    };
+
+    let statement = StatementNode::new(kind, consumed_span(tokens, ts));
+    Ok((ts, BlockPart::Statement(statement)))
+}
+
+/// Parses `if` and block expressions, which both start with a keyword or
+/// brace and end with a block.
+fn block_like_expression<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, ExpressionNode> {
+
+    let (t, mut ts) = pop_first(tokens);
+
+    let kind = match t.kind {
+        TokenKind::LBrace => {
+            assign!{ (ts, let block) = block(tokens, errors)? };
+            ExpressionKind::Block(block)
+        }
+        TokenKind::IfKeyword => {
+            assign!{ (ts, let condition) = expression(ts, errors)? };
+            assign!{ (ts, let then_block) = block(ts, errors)? };
+
+            let mut else_branch = None;
+            if let (&Token { kind: TokenKind::ElseKeyword, .. }, rest) = pop_first(ts) {
+                match pop_first(rest).0.kind {
+                    TokenKind::IfKeyword |
+                    TokenKind::LBrace => {
+                        assign!{ (ts, let exp) = block_like_expression(rest, errors)? };
+                        else_branch = Some(Box::new(exp));
+                    }
+                    _ => {
+                        let expected = [TokenKind::IfKeyword, TokenKind::LBrace];
+                        return Err(SyntaxError::unexpected(pop_first(rest).0, &expected));
+                    }
+                }
+            }
+
+            ExpressionKind::If(Box::new(condition), then_block, else_branch)
+        }
+        _ => {
+            let expected = [TokenKind::IfKeyword, TokenKind::LBrace];
+            return Err(SyntaxError::unexpected(t, &expected));
+        }
+    };
+
+    Ok((ts, ExpressionNode::new(kind, consumed_span(tokens, ts))))
+}
+
+/// Consumes the `;` which ends a statement after an expression.
+/// `alternatives` are listed as further tokens which would have been valid.
+/// At a token the lexer already reported the statement ends silently.
+fn end_of_statement<'a>(
+    tokens: &'a [Token],
+    alternatives: &[TokenKind],
+) -> SyntaxResult<&'a [Token]> {
+    match pop_first(tokens) {
+        (&Token { kind: TokenKind::Semicolon, .. }, ts) => Ok(ts),
+        (t, _) if reported_by_lexer(t) => Ok(tokens),
+        (t, _) => {
+            let mut terminators = alternatives.to_vec();
+            terminators.push(TokenKind::Semicolon);
+            let expected = expression_continuations(&terminators);
+            Err(SyntaxError::unexpected(t, &expected))
+        }
+    }
+}
+
+fn statement_start() -> Vec<TokenKind> {
+    let mut kinds = OPERAND_START.to_vec();
+    kinds.extend_from_slice(STATEMENT_START);
+    kinds
+}
+
+/// Parses all tokens as a single expression. Syntax errors do not stop the
+/// parser, the affected parts of the tree are replaced by
+/// `ExpressionKind::Error` and every error is returned.
+pub fn parse_expression(tokens: &[Token]) -> (ExpressionNode, Vec<SyntaxError>) {
+
+    let mut errors = Vec::new();
+    let mut tokens = tokens;
+
+    let exp = match expression(tokens, &mut errors) {
+        Ok((ts, exp)) => {
+            tokens = ts;
+            exp
+        }
+        Err(error) => {
+            report(&mut errors, error);
+            ExpressionNode::new(ExpressionKind::Error, consumed_span(tokens, tokens))
+        }
+    };
+
+    // everything after the expression is reported and skipped
+    loop {
+        let (t, ts) = pop_first(tokens);
+        match t.kind {
+            TokenKind::EndOfInput => break,
+            TokenKind::Error => {}
+            _ => {
+                let expected = expression_continuations(&[TokenKind::EndOfInput]);
+                report(&mut errors, SyntaxError::unexpected(t, &expected));
+            }
+        }
+        tokens = synchronize(ts);
+    }
+
+    (exp, errors)
+}
+
+pub fn expression<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, ExpressionNode> {
+    let (tokens, lhs) = primary_expression(tokens, errors)?;
+    binary_rhs_expression(tokens, lhs, 0, errors)
 }
 
 fn binary_rhs_expression<'a>(
     tokens: &'a [Token],
     lhs: ExpressionNode,
     min_precedence: u32,
+    errors: &mut Vec<SyntaxError>,
 ) -> ParseResult<'a, ExpressionNode> {
 
     let mut tokens = tokens;
     let mut lhs = lhs;
+    let mut previous: Option<(&OperatorInfo, &Token)> = None;
 
     while let Some((ts, op_info)) = read_binary_operator(tokens, min_precedence) {
-        let (ts, mut rhs) = primary_expression(ts)?;
-        let next_precedence = op_info.precedence + 1;
+
+        if let Some((previous_info, previous_token)) = previous {
+            if previous_info.associativity == Associativity::NonAssociative &&
+                previous_info.precedence == op_info.precedence
+            {
+                report(errors, chained_operator_error(previous_token, &tokens[0]));
+            }
+        }
+        previous = Some((op_info, &tokens[0]));
+
+        let (ts, mut rhs) = primary_expression(ts, errors)?;
+
+        // operators of the same precedence end up in the right hand side
+        // of right associative operators
+        let next_precedence = match op_info.associativity {
+            Associativity::Right => op_info.precedence,
+            Associativity::Left |
+            Associativity::NonAssociative => op_info.precedence + 1,
+        };
 
         if read_binary_operator(ts, next_precedence).is_some() {
-            assign!{ (tokens, rhs) = binary_rhs_expression(ts, rhs, next_precedence)? };
+            assign!{ (tokens, rhs) = binary_rhs_expression(ts, rhs, next_precedence, errors)? };
         } else {
             tokens = ts;
         }
 
-        lhs = ExpressionNode::Binary(op_info.operator, Box::new(lhs), Box::new(rhs));
+        let span = Span::new(lhs.span().start, rhs.span().end);
+        let kind = match op_info.operation {
+            Operation::Binary(operator) => {
+                ExpressionKind::Binary(operator, Box::new(lhs), Box::new(rhs))
+            }
+            Operation::Assign(operator) => {
+                if !lhs.is_place() {
+                    report(errors, invalid_assignment_target_error(&lhs));
+                }
+                ExpressionKind::Assign(operator, Box::new(lhs), Box::new(rhs))
+            }
+        };
+        lhs = ExpressionNode::new(kind, span);
     }
 
     return Ok((tokens, lhs));
 }
 
+/// Reports `a < b < c` and similar chains of non-associative operators, the
+/// expression is parsed as if the operators were left associative.
+fn chained_operator_error(previous: &Token, token: &Token) -> SyntaxError {
+    let message = format!(
+        "Operator {} cannot be chained with {}, use parentheses to group the operands",
+        token.kind,
+        previous.kind
+    );
+    SyntaxError::new(ErrorCode::ChainedOperator, token.start, token.end, message)
+        .with_related(previous.start, previous.end, "chained with this operator".to_string())
+}
+
+fn integer_out_of_range_error(token: &Token) -> SyntaxError {
+    let message = format!(
+        "Integer literal out of range, integers must be between {} and {}",
+        i32::MIN,
+        i32::MAX
+    );
+    SyntaxError::new(ErrorCode::IntegerOutOfRange, token.start, token.end, message)
+}
+
+fn invalid_assignment_target_error(target: &ExpressionNode) -> SyntaxError {
+    let message = "Invalid left-hand side of assignment, expected a variable, field or index".to_string();
+    let span = target.span();
+    SyntaxError::new(ErrorCode::InvalidAssignment, span.start, span.end, message)
+}
+
 fn read_binary_operator<'a>(
     tokens: &'a [Token],
     min_precedence: u32,
@@ -69,68 +637,425 @@ fn read_binary_operator<'a>(
     return None;
 }
 
-fn primary_expression<'a>(tokens: &'a [Token]) -> ParseResult<'a, ExpressionNode> {
-    let (mut t, mut ts) = pop_first(tokens)?;
+/// Parses a primary expression. If it is malformed the error is recorded, the
+/// parser skips ahead to the next synchronization point and an
+/// `ExpressionKind::Error` takes the place of the expression.
+fn primary_expression<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, ExpressionNode> {
+    match operand_expression(tokens, errors) {
+        Ok(result) => Ok(result),
+        Err(error) => {
+            report(errors, error);
+            let ts = synchronize(tokens);
+            let exp = ExpressionNode::new(ExpressionKind::Error, consumed_span(tokens, ts));
+            Ok((ts, exp))
+        }
+    }
+}
+
+fn operand_expression<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, ExpressionNode> {
+    let (mut t, mut ts) = pop_first(tokens);
 
     let mut prefix_operators = Vec::new();
     while let Some(unary) = t.kind.as_prefix_operator() {
-        prefix_operators.push(unary);
-        assign!{ (t, ts) = pop_first(ts)? };
+        prefix_operators.push((unary, t.start));
+        assign!{ (t, ts) = pop_first(ts) };
     }
 
-    let mut exp = match t.kind {
+    let mut operand_tokens = &tokens[prefix_operators.len()..];
+
+    let kind = match t.kind {
         TokenKind::Integer => {
-            let value = t.text.parse::<i32>().unwrap();
-            ExpressionNode::Integer(value)
+            // in `-2147483648 ** 2` the '-' applies to the power
+            let negated = prefix_operators.last().map(|&(unary, _)| unary) ==
+                Some(UnaryOperator::Negate) &&
+                pop_first(ts).0.kind != TokenKind::AsteriskAsterisk;
+            match t.text.parse::<i32>() {
+                Ok(value) => ExpressionKind::Integer(value),
+                // the smallest integer only fits after negating its literal,
+                // so the '-' becomes part of the literal
+                Err(_) if negated && t.text.parse::<i64>() == Ok(-(i32::MIN as i64)) => {
+                    prefix_operators.pop();
+                    operand_tokens = &tokens[prefix_operators.len()..];
+                    ExpressionKind::Integer(i32::MIN)
+                }
+                Err(_) => {
+                    report(errors, integer_out_of_range_error(t));
+                    ExpressionKind::Error
+                }
+            }
         }
         TokenKind::LParen => {
-            assign!{ (ts, let exp) = expression(ts)? };
+            assign!{ (ts, let exp) = expression(ts, errors)? };
+            ts = expect_closing(ts, TokenKind::RParen, &[], errors);
 
-            match ts.first() {
-                Some(&Token { kind: TokenKind::RParen, .. }) => {
-                    ts = &ts[1..];
-                    exp
-                }
-                Some(token) => return SyntaxError::from_token(token, "Missing ')'".to_string()),
-                None => return SyntaxError::from_token(t, "Expected ')' at EOF".to_string()),
-            }
+            // the parentheses become part of the span
+            exp.into_kind()
         }
         TokenKind::String => {
-            let value = t.text[1..t.text.len() - 1].to_string();
-            ExpressionNode::String(value)
+            let value = t.value.clone().expect("string tokens carry their value");
+            ExpressionKind::String(value)
+        }
+        TokenKind::StringStart => {
+            assign!{ (ts, let exp) = interpolated_string(operand_tokens, errors)? };
+            exp.into_kind()
         }
-        _ => return SyntaxError::from_token(&t, format!("Expected literal or '('")),
+        TokenKind::Identifier => ExpressionKind::Variable(t.text.clone()),
+        TokenKind::IfKeyword |
+        TokenKind::LBrace => {
+            assign!{ (ts, let exp) = block_like_expression(operand_tokens, errors)? };
+            exp.into_kind()
+        }
+        // the lexer already reported the error
+        TokenKind::Error => ExpressionKind::Error,
+        _ => return Err(SyntaxError::unexpected(t, OPERAND_START)),
     };
 
-    // unary postfix operators
-    while let Some(unary) = ts.first().and_then(|t| t.kind.as_postfix_operator()) {
-        exp = ExpressionNode::Unary(unary, Box::new(exp));
-        ts = &ts[1..];
+    let exp = ExpressionNode::new(kind, consumed_span(operand_tokens, ts));
+    assign!{ (ts, let exp) = postfix_expression(ts, exp, errors)? };
+    let mut exp = exp;
+
+    // the innermost '++' and '--' apply to the operand itself, the power is
+    // computed before the first operator which takes its value
+    let mut power_parsed = false;
+    for &(unary, start) in prefix_operators.iter().rev() {
+        if !power_parsed && !unary.modifies_operand() {
+            assign!{ (ts, exp) = binary_rhs_expression(ts, exp, POW_PRECEDENCE, errors)? };
+            power_parsed = true;
+        }
+        let span = Span::new(start, exp.span().end);
+        exp = ExpressionNode::new(ExpressionKind::Unary(unary, Box::new(exp)), span);
     }
 
-    for unary in prefix_operators.iter().rev() {
-        exp = ExpressionNode::Unary(*unary, Box::new(exp));
+    Ok((ts, exp))
+}
+
+/// Parses a string literal with interpolations, from its
+/// `TokenKind::StringStart` token up to its `TokenKind::StringEnd` token.
+fn interpolated_string<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, ExpressionNode> {
+
+    let (t, mut ts) = pop_first(tokens);
+    let mut parts = Vec::new();
+    push_string_text(&mut parts, t);
+
+    loop {
+        assign!{ (ts, let exp) = expression(ts, errors)? };
+        parts.push(StringPart::Expression(exp));
+
+        let (t, _) = pop_first(ts);
+        if !INTERPOLATION_END.contains(&t.kind) {
+            if !reported_by_lexer(t) {
+                // `TokenKind::StringMiddle` starts with the same '}'
+                let expected = expression_continuations(&[TokenKind::StringEnd]);
+                report(errors, SyntaxError::unexpected(t, &expected));
+            }
+            ts = skip_interpolation(ts);
+        }
+
+        let (t, rest) = pop_first(ts);
+        match t.kind {
+            TokenKind::StringMiddle => push_string_text(&mut parts, t),
+            TokenKind::StringEnd => {
+                push_string_text(&mut parts, t);
+                ts = rest;
+                break;
+            }
+            // the lexer already reported the unterminated string
+            _ => break,
+        }
+        ts = rest;
     }
 
+    let exp = ExpressionNode::new(ExpressionKind::Interpolated(parts), consumed_span(tokens, ts));
     Ok((ts, exp))
 }
 
-fn pop_first(tokens: &[Token]) -> SyntaxResult<(&Token, &[Token])> {
-    tokens.split_first().ok_or_else(|| {
-        let pos = UNKNOWN_POSITION;
-        SyntaxError {
-            start: pos,
-            end: pos,
-            message: format!("Unexpected EOF"),
+/// Skips the rest of a malformed interpolation up to the token which ends
+/// it, interpolated strings nested within it are skipped as a whole.
+fn skip_interpolation(tokens: &[Token]) -> &[Token] {
+
+    let mut tokens = tokens;
+    let mut depth = 0;
+
+    while let Some((t, ts)) = tokens.split_first() {
+        match t.kind {
+            TokenKind::EndOfInput => break,
+            TokenKind::StringStart => depth += 1,
+            TokenKind::StringEnd if depth > 0 => depth -= 1,
+            kind if depth == 0 && INTERPOLATION_END.contains(&kind) => break,
+            _ => {}
+        }
+        tokens = ts;
+    }
+
+    tokens
+}
+
+fn push_string_text(parts: &mut Vec<StringPart>, token: &Token) {
+    let text = token.value.clone().expect("string tokens carry their value");
+    if !text.is_empty() {
+        parts.push(StringPart::Text(text));
+    }
+}
+
+/// Consumes a token of the given kind or fails with an error naming it.
+fn expect(tokens: &[Token], kind: TokenKind) -> ParseResult<'_, &Token> {
+    match pop_first(tokens) {
+        (t, ts) if t.kind == kind => Ok((ts, t)),
+        (t, _) => Err(SyntaxError::unexpected(t, &[kind])),
+    }
+}
+
+/// Skips at least one token and then everything up to the start of the next
+/// item.
+fn skip_to_item(tokens: &[Token]) -> &[Token] {
+
+    let mut tokens = match tokens.split_first() {
+        Some((t, ts)) if t.kind != TokenKind::EndOfInput => ts,
+        _ => return tokens,
+    };
+
+    while let Some((t, ts)) = tokens.split_first() {
+        if ITEM_START.contains(&t.kind) || t.kind == TokenKind::DocComment {
+            break;
+        }
+        tokens = ts;
+    }
+
+    tokens
+}
+
+/// Returns the tokens starting at `position`, which must be within `tokens`.
+fn tokens_from(tokens: &[Token], position: TextPosition) -> &[Token] {
+    let index = tokens.iter().position(|t| t.start.index >= position.index);
+    &tokens[index.unwrap_or(tokens.len() - 1)..]
+}
+
+/// Skips the rest of a malformed statement up to and including the next `;`.
+/// Nested blocks are skipped as a whole, a block ending the statement ends
+/// the skip and so does the end of the enclosing block or item.
+fn skip_statement(tokens: &[Token]) -> &[Token] {
+
+    let mut tokens = tokens;
+    let mut depth = 0;
+
+    while let Some((t, ts)) = tokens.split_first() {
+        match t.kind {
+            TokenKind::EndOfInput |
+            TokenKind::FnKeyword => break,
+            TokenKind::Semicolon if depth == 0 => return ts,
+            TokenKind::LBrace => depth += 1,
+            TokenKind::RBrace if depth == 0 => break,
+            TokenKind::RBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    return ts;
+                }
+            }
+            _ => {}
+        }
+        tokens = ts;
+    }
+
+    tokens
+}
+
+/// Applies the postfix operators following `operand` from left to right, so
+/// `a.b(c)[d]++` indexes the result of the call.
+fn postfix_expression<'a>(
+    tokens: &'a [Token],
+    operand: ExpressionNode,
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, ExpressionNode> {
+
+    let mut tokens = tokens;
+    let mut exp = operand;
+
+    loop {
+        let start = exp.span().start;
+        let (t, mut ts) = pop_first(tokens);
+
+        let kind = match t.kind {
+            TokenKind::LParen => {
+                assign!{ (ts, let arguments) = argument_list(ts, errors)? };
+                ExpressionKind::Call(Box::new(exp), arguments)
+            }
+            TokenKind::LBracket => {
+                assign!{ (ts, let index) = expression(ts, errors)? };
+                ts = expect_closing(ts, TokenKind::RBracket, &[], errors);
+                ExpressionKind::Index(Box::new(exp), Box::new(index))
+            }
+            TokenKind::Dot => {
+                match pop_first(ts) {
+                    (name, rest) if name.kind == TokenKind::Identifier => {
+                        ts = rest;
+                        ExpressionKind::Field(Box::new(exp), name.text.clone())
+                    }
+                    (name, _) => {
+                        report(errors, SyntaxError::unexpected(name, &[TokenKind::Identifier]));
+                        ExpressionKind::Error
+                    }
+                }
+            }
+            _ => {
+                match t.kind.as_postfix_operator() {
+                    Some(unary) => ExpressionKind::Unary(unary, Box::new(exp)),
+                    None => return Ok((tokens, exp)),
+                }
+            }
+        };
+
+        let span = Span::new(start, consumed_span(tokens, ts).end);
+        exp = ExpressionNode::new(kind, span);
+        tokens = ts;
+    }
+}
+
+/// Parses the arguments of a call up to and including the closing
+/// parenthesis, a trailing comma is allowed.
+fn argument_list<'a>(
+    tokens: &'a [Token],
+    errors: &mut Vec<SyntaxError>,
+) -> ParseResult<'a, Vec<ExpressionNode>> {
+
+    let mut tokens = tokens;
+    let mut arguments = Vec::new();
+
+    loop {
+        if let (&Token { kind: TokenKind::RParen, .. }, ts) = pop_first(tokens) {
+            return Ok((ts, arguments));
+        }
+
+        assign!{ (tokens, let argument) = expression(tokens, errors)? };
+        arguments.push(argument);
+
+        match pop_first(tokens) {
+            (&Token { kind: TokenKind::Comma, .. }, ts) => tokens = ts,
+            _ => {
+                let ts = expect_closing(tokens, TokenKind::RParen, &[TokenKind::Comma], errors);
+                return Ok((ts, arguments));
+            }
         }
-    })
+    }
+}
+
+/// Consumes the `closing` bracket of a bracketed expression. If it is
+/// missing the error is reported, unless the lexer already reported the token
+/// found instead, and the parser skips ahead, past the bracket if it can be
+/// found. `alternatives` are listed as further tokens
+/// which would have been valid.
+fn expect_closing<'a>(
+    tokens: &'a [Token],
+    closing: TokenKind,
+    alternatives: &[TokenKind],
+    errors: &mut Vec<SyntaxError>,
+) -> &'a [Token] {
+
+    let t = match pop_first(tokens) {
+        (t, ts) if t.kind == closing => return ts,
+        (t, _) => t,
+    };
+
+    if !reported_by_lexer(t) {
+        let mut terminators = alternatives.to_vec();
+        terminators.push(closing);
+        let expected = expression_continuations(&terminators);
+        report(errors, SyntaxError::unexpected(t, &expected));
+    }
+
+    let ts = synchronize(tokens);
+    match ts.split_first() {
+        Some((t, rest)) if t.kind == closing => rest,
+        _ => ts,
+    }
+}
+
+/// Skips tokens until the next synchronization point. The first token is
+/// always skipped unless it is a synchronization point itself.
+fn synchronize(tokens: &[Token]) -> &[Token] {
+
+    let mut tokens = match tokens.split_first() {
+        Some((t, ts)) if !SYNCHRONIZATION_POINTS.contains(&t.kind) => ts,
+        _ => return tokens,
+    };
+
+    while let Some((t, ts)) = tokens.split_first() {
+        if SYNCHRONIZATION_POINTS.contains(&t.kind) {
+            break;
+        }
+        tokens = ts;
+    }
+
+    tokens
+}
+
+/// Returns the span of the tokens consumed from `tokens` to get to `rest`.
+/// If nothing was consumed the span is empty and sits at the next token.
+fn consumed_span(tokens: &[Token], rest: &[Token]) -> Span {
+    match tokens.len() - rest.len() {
+        0 => Span::new(tokens[0].start, tokens[0].start),
+        count => Span::new(tokens[0].start, tokens[count - 1].end),
+    }
+}
+
+/// Whether the lexer already reported the token `found`: an invalid token or
+/// the end of an input which ended within an unterminated string or comment.
+fn reported_by_lexer(found: &Token) -> bool {
+    found.kind == TokenKind::Error || (found.kind == TokenKind::EndOfInput && found.unterminated)
+}
+
+/// Records an error unless an earlier error already points at the same
+/// position, in which case the new one is most likely a consequence of it.
+fn report(errors: &mut Vec<SyntaxError>, error: SyntaxError) {
+    if errors.last().map(|last| last.start) != Some(error.start) {
+        errors.push(error);
+    }
+}
+
+/// Token kinds which may follow a complete expression and continue it,
+/// followed by the `terminators` which may end it.
+fn expression_continuations(terminators: &[TokenKind]) -> Vec<TokenKind> {
+    let mut kinds: Vec<_> = OPERATORS.keys().cloned().collect();
+    kinds.extend_from_slice(POSTFIX_OPERATORS);
+    kinds.extend_from_slice(terminators);
+    kinds
+}
+
+fn pop_first(tokens: &[Token]) -> (&Token, &[Token]) {
+    tokens.split_first().expect(
+        "token stream must end with TokenKind::EndOfInput",
+    )
 }
 
 #[derive(Debug)]
 struct OperatorInfo {
     token_kind: TokenKind,
-    operator: BinaryOperator,
+    operation: Operation,
     precedence: u32,
+    associativity: Associativity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operation {
+    Binary(BinaryOperator),
+    /// Plain assignment or, with an operator, compound assignment like `+=`.
+    Assign(Option<BinaryOperator>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Associativity {
+    Left,
+    Right,
+    /// The operator cannot be chained with operators of the same precedence.
+    NonAssociative,
 }
 
 impl PrimaryKey<TokenKind> for OperatorInfo {
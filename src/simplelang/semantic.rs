@@ -0,0 +1,141 @@
+use simplelang::*;
+use simplelang::ast::*;
+use simplelang::diagnostics::*;
+
+/// Checks rules which the grammar alone does not enforce and returns a
+/// diagnostic for every violation.
+pub fn check_program(items: &[Item]) -> Vec<Diagnostic> {
+    let mut checker = Checker::new();
+    for item in items {
+        match *item {
+            Item::Function(ref function) => checker.visit_block(&function.body),
+        }
+    }
+    checker.diagnostics
+}
+
+/// Like `check_program`, for a single expression.
+pub fn check_expression(expr: &ExpressionNode) -> Vec<Diagnostic> {
+    let mut checker = Checker::new();
+    checker.visit_expression(expr);
+    checker.diagnostics
+}
+
+struct Checker {
+    diagnostics: Vec<Diagnostic>,
+    /// Number of loops around the statement being checked.
+    loop_depth: usize,
+}
+
+impl Checker {
+    fn new() -> Self {
+        Checker {
+            diagnostics: Vec::new(),
+            loop_depth: 0,
+        }
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        for statement in &block.statements {
+            self.visit_statement(statement);
+        }
+        if let Some(ref value) = block.value {
+            self.visit_expression(value);
+        }
+    }
+
+    fn visit_statement(&mut self, statement: &StatementNode) {
+        match *statement.kind() {
+            Statement::Let(_, None) => {}
+            Statement::Let(_, Some(ref value)) => self.visit_expression(value),
+            Statement::Expression(ref expr) => self.visit_expression(expr),
+            Statement::While(ref condition, ref body) => {
+                self.visit_expression(condition);
+                self.visit_loop_body(body);
+            }
+            Statement::Loop(ref body) => self.visit_loop_body(body),
+            Statement::Break => self.check_inside_loop("break", statement.span()),
+            Statement::Continue => self.check_inside_loop("continue", statement.span()),
+            Statement::Return(None) => {}
+            Statement::Return(Some(ref value)) => self.visit_expression(value),
+        }
+    }
+
+    fn visit_loop_body(&mut self, body: &Block) {
+        self.loop_depth += 1;
+        self.visit_block(body);
+        self.loop_depth -= 1;
+    }
+
+    fn visit_expression(&mut self, expr: &ExpressionNode) {
+        match *expr.kind() {
+            ExpressionKind::Error |
+            ExpressionKind::Integer(_) |
+            ExpressionKind::String(_) |
+            ExpressionKind::Variable(_) => {}
+            ExpressionKind::Binary(_, ref lhs, ref rhs) |
+            ExpressionKind::Assign(_, ref lhs, ref rhs) |
+            ExpressionKind::Index(ref lhs, ref rhs) => {
+                self.visit_expression(lhs);
+                self.visit_expression(rhs);
+            }
+            ExpressionKind::Call(ref callee, ref arguments) => {
+                self.visit_expression(callee);
+                for argument in arguments {
+                    self.visit_expression(argument);
+                }
+            }
+            ExpressionKind::Field(ref target, _) => self.visit_expression(target),
+            ExpressionKind::Interpolated(ref parts) => {
+                for part in parts {
+                    if let StringPart::Expression(ref expr) = *part {
+                        self.visit_expression(expr);
+                    }
+                }
+            }
+            ExpressionKind::Block(ref block) => self.visit_block(block),
+            ExpressionKind::If(ref condition, ref then_block, ref else_branch) => {
+                self.visit_expression(condition);
+                self.visit_block(then_block);
+                if let Some(ref else_branch) = *else_branch {
+                    self.visit_expression(else_branch);
+                }
+            }
+            ExpressionKind::Unary(operator, ref operand) => {
+                if operator.modifies_operand() && !operand.is_place() {
+                    self.diagnostics.push(not_a_place(operator, operand));
+                }
+                self.visit_expression(operand);
+            }
+        }
+    }
+
+    fn check_inside_loop(&mut self, keyword: &str, span: Span) {
+        if self.loop_depth == 0 {
+            let diagnostic = Diagnostic::error(
+                ErrorCode::OutsideOfLoop,
+                format!("'{}' outside of a loop", keyword),
+                span.start,
+                span.end,
+            ).with_label(format!("cannot '{}' outside of a loop", keyword));
+            self.diagnostics.push(diagnostic);
+        }
+    }
+}
+
+fn not_a_place(operator: UnaryOperator, operand: &ExpressionNode) -> Diagnostic {
+
+    let (symbol, verb) = match operator {
+        UnaryOperator::PreInc | UnaryOperator::PostInc => ("++", "incremented"),
+        _ => ("--", "decremented"),
+    };
+
+    let span = operand.span();
+    Diagnostic::error(
+        ErrorCode::NotAPlace,
+        format!("Operand of '{}' must be a variable, field or index", symbol),
+        span.start,
+        span.end,
+    ).with_label(format!("this expression cannot be {}", verb))
+        .with_help(format!("'{}' modifies a variable in place", symbol))
+}
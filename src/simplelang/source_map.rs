@@ -0,0 +1,138 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use simplelang::*;
+
+/// Identifies a file registered in a `SourceMap`.
+///
+/// Sources lexed without a source map belong to `FileId::default()`, which
+/// is reserved and never handed out by `SourceMap::add_file`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct FileId(pub(super) usize);
+
+impl Default for FileId {
+    fn default() -> Self {
+        FileId(usize::MAX)
+    }
+}
+
+#[derive(Debug)]
+pub struct SourceFile {
+    id: FileId,
+    name: String,
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(id: FileId, name: String, text: String) -> Self {
+
+        let mut line_starts = vec![0];
+        for (index, c) in text.char_indices() {
+            if c == '\n' {
+                line_starts.push(index + 1);
+            }
+        }
+
+        SourceFile {
+            id,
+            name,
+            text,
+            line_starts,
+        }
+    }
+
+    pub fn id(&self) -> FileId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the zero-based line which contains the byte offset `index`.
+    pub fn line_of(&self, index: usize) -> usize {
+        match self.line_starts.binary_search(&index) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        }
+    }
+
+    /// Returns the text of a zero-based line without its line terminator.
+    pub fn line_text(&self, line: usize) -> Option<&str> {
+
+        let start = *self.line_starts.get(line)?;
+        let end = self.line_starts.get(line + 1).cloned().unwrap_or(
+            self.text.len(),
+        );
+
+        let text = &self.text[start..end];
+        let text = text.trim_end_matches('\n');
+        let text = text.trim_end_matches('\r');
+        Some(text)
+    }
+}
+
+/// Resolved, human readable form of a `TextPosition`.
+///
+/// `line` and `column` are one-based, as they are presented to users.
+#[derive(Debug, Eq, PartialEq)]
+pub struct SourceLocation<'a> {
+    pub file_name: &'a str,
+    pub line: usize,
+    pub column: usize,
+    pub line_text: &'a str,
+}
+
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap { files: Vec::new() }
+    }
+
+    pub fn add_file(&mut self, name: String, text: String) -> FileId {
+        let id = FileId(self.files.len());
+        self.files.push(SourceFile::new(id, name, text));
+        id
+    }
+
+    pub fn load_file(&mut self, path: &str) -> io::Result<FileId> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Ok(self.add_file(path.to_string(), text))
+    }
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        self.files.get(id.0).expect(
+            "file is not registered in this source map",
+        )
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    pub fn resolve(&self, position: TextPosition) -> SourceLocation<'_> {
+
+        let file = self.file(position.file);
+        let index = position.index.min(file.text.len());
+        let line = file.line_of(index);
+        let line_start = file.line_starts[line];
+        let column = file.text[line_start..index].chars().count();
+
+        SourceLocation {
+            file_name: &file.name,
+            line: line + 1,
+            column: column + 1,
+            line_text: file.line_text(line).unwrap_or(""),
+        }
+    }
+}
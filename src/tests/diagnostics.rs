@@ -0,0 +1,330 @@
+use simplelang::*;
+use simplelang::diagnostics::*;
+use simplelang::lexer;
+use simplelang::source_map::*;
+
+#[test]
+fn render_syntax_error() {
+    let (source_map, id) = source("1 +\n2 $ 3\n");
+    let (_, errors) = lexer::lex_file(source_map.file(id));
+    let error = &errors[0];
+
+    assert_render(
+        &source_map,
+        &Diagnostic::from(error),
+        "error[E0001]: Unexpected symbol '$'
+ --> test.sl:2:3
+  |
+2 | 2 $ 3
+  |   ^
+  |
+",
+    );
+}
+
+#[test]
+fn render_unterminated_raw_string() {
+    let (source_map, id) = source("x = r#\"abc\n\ny");
+    let (_, errors) = lexer::lex_file(source_map.file(id));
+
+    assert_render(
+        &source_map,
+        &Diagnostic::from(&errors[0]),
+        "error[E0004]: Unterminated raw string literal, expected '\"#' to close the 'r#\"' opened here
+ --> test.sl:1:5
+  |
+1 | x = r#\"abc
+  |     ^^^
+...
+3 | y
+  |  - the input ends before the string is closed
+  |
+",
+    );
+}
+
+#[test]
+fn render_range_with_label_note_and_help() {
+    let (source_map, id) = source("x = 123 + y");
+    let mut diagnostic =
+        Diagnostic::error(ErrorCode::UnexpectedCharacter, "Broken".to_string(), pos(id, 4), pos(id, 7))
+            .with_label("this literal".to_string())
+            .with_help("some help".to_string());
+    diagnostic.notes.push("some note".to_string());
+
+    assert_render(
+        &source_map,
+        &diagnostic,
+        "error[E0001]: Broken
+ --> test.sl:1:5
+  |
+1 | x = 123 + y
+  |     ^^^ this literal
+  |
+  = note: some note
+  = help: some help
+",
+    );
+}
+
+#[test]
+fn render_secondary_label() {
+    let (source_map, id) = source("(1 +\n\n\n2");
+    let diagnostic =
+        Diagnostic::error(ErrorCode::UnexpectedToken, "Missing ')'".to_string(), pos(id, 8), pos(id, 8))
+            .with_secondary(pos(id, 0), pos(id, 1), "opened here".to_string());
+
+    assert_render(
+        &source_map,
+        &diagnostic,
+        "error[E0100]: Missing ')'
+ --> test.sl:4:2
+  |
+1 | (1 +
+  | - opened here
+...
+4 | 2
+  |  ^
+  |
+",
+    );
+}
+
+#[test]
+fn render_multi_line_span() {
+    let (source_map, id) = source("ab\ncdef\n");
+    let diagnostic =
+        Diagnostic::error(ErrorCode::UnexpectedToken, "Span".to_string(), pos(id, 1), pos(id, 5))
+            .with_label("here".to_string());
+
+    assert_render(
+        &source_map,
+        &diagnostic,
+        "error[E0100]: Span
+ --> test.sl:1:2
+  |
+1 | ab
+  |  ^
+2 | cdef
+  | ^^ here
+  |
+",
+    );
+}
+
+#[test]
+fn render_long_span() {
+    let (source_map, id) = source("fn f() {\n    a;\n    b;\n    c;\n    d;\n}\n");
+    let diagnostic =
+        Diagnostic::error(ErrorCode::UnexpectedToken, "Span".to_string(), pos(id, 0), pos(id, 38))
+            .with_label("here".to_string());
+
+    assert_render(
+        &source_map,
+        &diagnostic,
+        "error[E0100]: Span
+ --> test.sl:1:1
+  |
+1 | fn f() {
+  | ^^^^^^^^
+...
+6 | }
+  | ^ here
+  |
+",
+    );
+}
+
+#[test]
+fn render_keeps_tabs_aligned() {
+    let (source_map, id) = source("\t\tx");
+    let diagnostic =
+        Diagnostic::error(ErrorCode::UnexpectedCharacter, "Tab".to_string(), pos(id, 2), pos(id, 3));
+
+    assert_render(
+        &source_map,
+        &diagnostic,
+        "error[E0001]: Tab
+ --> test.sl:1:3
+  |
+1 | \t\tx
+  | \t\t^
+  |
+",
+    );
+}
+
+#[test]
+fn render_warning() {
+    let (source_map, id) = source("fn f() {\n    /// Comment.\n}");
+    let (tokens, _) = lexer::lex_file(source_map.file(id));
+    let (_, errors) = parser::program(&tokens);
+
+    assert_render(
+        &source_map,
+        &Diagnostic::from(&errors[0]),
+        "warning[E0104]: Doc comments are only allowed in front of functions
+ --> test.sl:2:5
+  |
+2 |     /// Comment.
+  |     ^^^^^^^^^^^^
+  |
+",
+    );
+
+    let rendered = Renderer::new(true).render(&source_map, &Diagnostic::from(&errors[0]));
+    assert!(rendered.starts_with("\x1b[1;33mwarning[E0104]\x1b[0m"));
+    assert!(rendered.contains("\x1b[1;33m^^^^^^^^^^^^\x1b[0m"));
+}
+
+#[test]
+fn render_main_with_parameters() {
+    let (source_map, id) = source("fn main(a, b) {\n    let c = a;\n    c + b\n}\n");
+    let (tokens, _) = lexer::lex_file(source_map.file(id));
+    let (module, _) = parser::program(&tokens);
+    let error = interpreter::run(&module.items, id, interpreter::Overflow::Checked)
+        .expect_err("Expected a runtime error");
+
+    assert_render(
+        &source_map,
+        &Diagnostic::from(&error),
+        "error[E0308]: The 'main' function must not take parameters
+ --> test.sl:1:9
+  |
+1 | fn main(a, b) {
+  |         ^^^^
+  |
+",
+    );
+}
+
+#[test]
+fn render_colors() {
+    let (source_map, id) = source("x");
+    let diagnostic =
+        Diagnostic::error(ErrorCode::UnexpectedCharacter, "Color".to_string(), pos(id, 0), pos(id, 1));
+    let rendered = Renderer::new(true).render(&source_map, &diagnostic);
+
+    assert!(rendered.starts_with("\x1b[1;31merror[E0001]\x1b[0m: \x1b[1mColor\x1b[0m\n"));
+    assert!(rendered.contains("\x1b[1;31m^\x1b[0m"));
+}
+
+#[test]
+fn json_syntax_error() {
+    let (source_map, id) = source("1 +\n2 $ 3\n");
+    let (_, errors) = lexer::lex_file(source_map.file(id));
+    let error = &errors[0];
+
+    assert_eq!(
+        to_json(&source_map, &Diagnostic::from(error)),
+        concat!(
+            r#"{"severity":"error","code":"E0001","message":"Unexpected symbol '$'","#,
+            r#""file":"test.sl","start":{"offset":6,"line":2,"column":3},"#,
+            r#""end":{"offset":7,"line":2,"column":4},"label":null,"#,
+            r#""related":[],"notes":[],"help":[],"expected":[]}"#
+        )
+    );
+}
+
+#[test]
+fn json_unexpected_token() {
+    let (source_map, id) = source("fn main() { let x 1 }");
+    let (tokens, _) = lexer::lex_file(source_map.file(id));
+    let (_, errors) = parser::program(&tokens);
+    let json = to_json(&source_map, &Diagnostic::from(&errors[0]));
+
+    assert!(json.contains(r#""code":"E0100""#), "{}", json);
+    assert!(json.ends_with(r#""expected":["'='","';'"]}"#), "{}", json);
+}
+
+#[test]
+fn json_chained_operator() {
+    let (source_map, id) = source("a < b < c");
+    let (tokens, _) = lexer::lex_file(source_map.file(id));
+    let (_, errors) = parser::parse_expression(&tokens);
+
+    assert_eq!(
+        to_json(&source_map, &Diagnostic::from(&errors[0])),
+        concat!(
+            r#"{"severity":"error","code":"E0101","#,
+            r#""message":"Operator '<' cannot be chained with '<', use parentheses to group the operands","#,
+            r#""file":"test.sl","start":{"offset":6,"line":1,"column":7},"#,
+            r#""end":{"offset":7,"line":1,"column":8},"label":null,"#,
+            r#""related":[{"file":"test.sl","start":{"offset":2,"line":1,"column":3},"#,
+            r#""end":{"offset":3,"line":1,"column":4},"message":"chained with this operator"}],"#,
+            r#""notes":[],"help":[],"expected":[]}"#
+        )
+    );
+}
+
+#[test]
+fn diagnostic_codes() {
+    let code = |input: &str| {
+        let (source_map, id) = source(input);
+        let (tokens, mut errors) = lexer::lex_file(source_map.file(id));
+        let (module, parse_errors) = parser::program(&tokens);
+        errors.extend(parse_errors);
+
+        let mut diagnostics: Vec<_> = errors.iter().map(Diagnostic::from).collect();
+        diagnostics.extend(semantic::check_program(&module.items));
+        if diagnostics.is_empty() {
+            let error = interpreter::run(&module.items, id, interpreter::Overflow::Checked)
+                .expect_err("Expected a runtime error");
+            diagnostics.push(Diagnostic::from(&error));
+        }
+        diagnostics[0].code.as_str()
+    };
+
+    assert_eq!(code("fn main() { \"a\\q\" }"), "E0003");
+    assert_eq!(code("fn main() { /* a"), "E0005");
+    assert_eq!(code("fn main() { 1 + }"), "E0100");
+    assert_eq!(code("fn main() { 3000000000 }"), "E0102");
+    assert_eq!(code("fn main() { break; }"), "E0200");
+    assert_eq!(code("fn main() { 1 + 2++ }"), "E0201");
+    assert_eq!(code("fn main() { x }"), "E0302");
+    assert_eq!(code("fn main() { 1 / 0 }"), "E0305");
+    assert_eq!(code("fn f() {}"), "E0308");
+}
+
+#[test]
+fn json_related_spans_and_escapes() {
+    let (source_map, id) = source("(\"\\\"\n");
+    let message = "a \"quoted\"\tmessage".to_string();
+    let mut diagnostic = Diagnostic::error(ErrorCode::UnexpectedCharacter, message, pos(id, 1), pos(id, 4))
+        .with_label("label".to_string())
+        .with_secondary(pos(id, 0), pos(id, 1), "opened here".to_string())
+        .with_help("\u{1}".to_string());
+    diagnostic.notes.push("line\nbreak".to_string());
+
+    assert_eq!(
+        to_json(&source_map, &diagnostic),
+        concat!(
+            r#"{"severity":"error","code":"E0001","message":"a \"quoted\"\tmessage","#,
+            r#""file":"test.sl","start":{"offset":1,"line":1,"column":2},"#,
+            r#""end":{"offset":4,"line":1,"column":5},"label":"label","#,
+            r#""related":[{"file":"test.sl","start":{"offset":0,"line":1,"column":1},"#,
+            r#""end":{"offset":1,"line":1,"column":2},"message":"opened here"}],"#,
+            r#""notes":["line\nbreak"],"help":["\u0001"],"expected":[]}"#
+        )
+    );
+}
+
+fn assert_render(source_map: &SourceMap, diagnostic: &Diagnostic, expected: &str) {
+    let rendered = Renderer::new(false).render(source_map, diagnostic);
+    assert_eq!(rendered, expected);
+}
+
+fn source(text: &str) -> (SourceMap, FileId) {
+    let mut source_map = SourceMap::new();
+    let id = source_map.add_file("test.sl".to_string(), text.to_string());
+    (source_map, id)
+}
+
+fn pos(file: FileId, index: usize) -> TextPosition {
+    TextPosition {
+        file,
+        index,
+        line: 0,
+        column: 0,
+    }
+}
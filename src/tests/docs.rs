@@ -0,0 +1,29 @@
+use simplelang::*;
+use simplelang::docs;
+
+#[test]
+fn render_markdown() {
+    let input = "//! The module.\n//!\n//!  Indented.\n\
+                 /// Adds two numbers.\nfn add(a, b) { a + b }\n\
+                 fn undocumented() {}";
+    let (tokens, errors) = lexer::lex(input.as_bytes());
+    assert!(errors.is_empty(), "Lex errors {:?}", errors);
+    let (module, errors) = parser::program(&tokens);
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+
+    assert_eq!(
+        docs::render_markdown("test.sl", &module),
+        "# test.sl
+
+The module.
+
+ Indented.
+
+## fn add(a, b)
+
+Adds two numbers.
+
+## fn undocumented()
+"
+    );
+}
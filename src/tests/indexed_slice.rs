@@ -81,6 +81,17 @@ fn slice_first() {
     assert_eq!(slice.first(), Some(&'A'));
 }
 
+#[test]
+fn slice_try_get() {
+
+    let text = char_vec("AB");
+    let slice = IndexedSlice::from_slice(text.as_slice());
+
+    assert_eq!(slice.try_get(0), Some(&'A'));
+    assert_eq!(slice.try_get(1), Some(&'B'));
+    assert_eq!(slice.try_get(2), None);
+}
+
 fn assert_slice(slice: IndexedSlice<char>, text: &str, position: usize, len: usize) {
     assert_eq!(slice.to_string(), text);
     assert_eq!(slice.position(), position);
@@ -0,0 +1,326 @@
+use simplelang::*;
+use simplelang::interpreter::*;
+use simplelang::source_map::FileId;
+
+#[test]
+fn eval_literals() {
+    assert_eq!(eval_ok("42"), Value::Integer(42));
+    assert_eq!(eval_ok("\"text\""), Value::String("text".to_string()));
+}
+
+#[test]
+fn eval_arithmetic() {
+    assert_eq!(eval_ok("1 + 2 * 3"), Value::Integer(7));
+    assert_eq!(eval_ok("(1 + 2) * 3"), Value::Integer(9));
+    assert_eq!(eval_ok("7 / 2 - 7 % 2"), Value::Integer(2));
+    assert_eq!(eval_ok("2 ** 3 ** 2"), Value::Integer(512));
+    assert_eq!(eval_ok("1 << 4 | 3 & 6 ^ 1"), Value::Integer(19));
+    assert_eq!(eval_ok("-16 >> 2"), Value::Integer(-4));
+    assert_eq!(eval_ok("-2 ** 2"), Value::Integer(-4));
+}
+
+#[test]
+fn eval_unary_operators() {
+    assert_eq!(eval_ok("-(2 + 3)"), Value::Integer(-5));
+    assert_eq!(eval_ok("+7"), Value::Integer(7));
+    assert_eq!(eval_ok("~0"), Value::Integer(-1));
+    assert_eq!(eval_ok("!(1 < 2)"), Value::Boolean(false));
+    assert_eq!(eval_ok("{ let x = 2; ++x ** 2 }"), Value::Integer(9));
+    assert_eq!(eval_ok("{ let x = 2; -x++ ** 2 }"), Value::Integer(-4));
+}
+
+#[test]
+fn eval_comparisons() {
+    assert_eq!(eval_ok("1 < 2"), Value::Boolean(true));
+    assert_eq!(eval_ok("2 <= 1"), Value::Boolean(false));
+    assert_eq!(eval_ok("\"a\" < \"b\""), Value::Boolean(true));
+    assert_eq!(eval_ok("\"a\" == \"a\""), Value::Boolean(true));
+    assert_eq!(eval_ok("(1 < 2) != (2 < 1)"), Value::Boolean(true));
+}
+
+#[test]
+fn eval_strings() {
+    assert_eq!(eval_ok("\"ab\" + \"cd\""), Value::String("abcd".to_string()));
+}
+
+#[test]
+fn eval_interpolated_strings() {
+    assert_eq!(
+        eval_ok(r#"{ let name = "World"; let count = 2; "Hello, ${name}! You have ${count + 1} items" }"#),
+        Value::String("Hello, World! You have 3 items".to_string())
+    );
+    assert_eq!(
+        eval_ok(r#""${1 < 2} ${{}} ${ "in${"ner"}" }""#),
+        Value::String("true () inner".to_string())
+    );
+    assert_eq!(
+        eval_err(r#""a ${1 / 0}""#),
+        ("Division by zero".to_string(), (5, 10))
+    );
+}
+
+#[test]
+fn eval_logical_operators_short_circuit() {
+    // `x` is not defined, so evaluating it would fail
+    assert_eq!(eval_ok("1 > 2 && x"), Value::Boolean(false));
+    assert_eq!(eval_ok("1 < 2 || x"), Value::Boolean(true));
+    assert_eq!(eval_ok("1 < 2 && 3 < 4"), Value::Boolean(true));
+}
+
+#[test]
+fn eval_blocks_and_if() {
+    assert_eq!(eval_ok("{ let a = 2; a * a }"), Value::Integer(4));
+    assert_eq!(eval_ok("{ 1; }"), Value::Unit);
+    assert_eq!(eval_ok("if 1 < 2 { 10 } else { 20 }"), Value::Integer(10));
+    assert_eq!(eval_ok("if 1 > 2 { 10 } else if 1 > 3 { 20 } else { 30 }"), Value::Integer(30));
+    assert_eq!(eval_ok("if 1 > 2 { 10 }"), Value::Unit);
+}
+
+#[test]
+fn eval_errors() {
+    assert_eq!(eval_err("1 + 10 / (2 - 2)"), ("Division by zero".to_string(), (4, 16)));
+    assert_eq!(
+        eval_err("1 + \"a\""),
+        ("Cannot apply '+' to values of type integer and string".to_string(), (0, 7))
+    );
+    assert_eq!(
+        eval_err("-\"a\""),
+        ("Cannot apply '-' to a value of type string".to_string(), (0, 4))
+    );
+    assert_eq!(eval_err("a + 1"), ("Cannot find variable 'a'".to_string(), (0, 1)));
+    assert_eq!(
+        eval_err("if 1 { 2 }"),
+        ("Expected a boolean but found integer".to_string(), (3, 4))
+    );
+}
+
+#[test]
+fn eval_checked_overflow() {
+    let overflow = |input| eval_err(input).0;
+    assert_eq!(eval_err("1 + 2147483647"), ("Integer overflow in '+'".to_string(), (0, 14)));
+    assert_eq!(overflow("-2147483648 - 1"), "Integer overflow in '-'");
+    assert_eq!(overflow("65536 * 65536"), "Integer overflow in '*'");
+    assert_eq!(overflow("-2147483648 / -1"), "Integer overflow in '/'");
+    assert_eq!(overflow("2 ** 31"), "Integer overflow in '**'");
+    assert_eq!(overflow("1 << 32"), "Integer overflow in '<<'");
+    assert_eq!(overflow("1 >> -1"), "Integer overflow in '>>'");
+    assert_eq!(overflow("-(-2147483648)"), "Integer overflow in '-'");
+    assert_eq!(overflow("{ let a = 2147483647; a++ }"), "Integer overflow in '++'");
+    assert_eq!(overflow("{ let a = -2147483648; --a }"), "Integer overflow in '--'");
+    assert_eq!(overflow("{ let a = 2147483647; a += 1; a }"), "Integer overflow in '+'");
+
+    assert_eq!(eval_ok("-2147483648 % -1"), Value::Integer(0));
+    assert_eq!(eval_ok("2147483647 + -2147483648"), Value::Integer(-1));
+    assert_eq!(eval_ok("(-2) ** 31"), Value::Integer(-2147483648));
+    assert_eq!(overflow("-2 ** 31"), "Integer overflow in '**'");
+}
+
+#[test]
+fn eval_wrapping_overflow() {
+    let wrapping = |input| eval(input, Overflow::Wrapping).expect("Runtime error");
+    assert_eq!(wrapping("2147483647 + 1"), Value::Integer(-2147483648));
+    assert_eq!(wrapping("-2147483648 - 1"), Value::Integer(2147483647));
+    assert_eq!(wrapping("65536 * 65537"), Value::Integer(65536));
+    assert_eq!(wrapping("-2147483648 / -1"), Value::Integer(-2147483648));
+    assert_eq!(wrapping("2 ** 32"), Value::Integer(0));
+    assert_eq!(wrapping("1 << 33"), Value::Integer(2));
+    assert_eq!(wrapping("-(-2147483648)"), Value::Integer(-2147483648));
+    assert_eq!(wrapping("{ let a = 2147483647; ++a }"), Value::Integer(-2147483648));
+}
+
+#[test]
+fn eval_saturating_overflow() {
+    let saturating = |input| eval(input, Overflow::Saturating).expect("Runtime error");
+    assert_eq!(saturating("2147483647 + 1"), Value::Integer(2147483647));
+    assert_eq!(saturating("-2147483648 - 1"), Value::Integer(-2147483648));
+    assert_eq!(saturating("-65536 * 65536"), Value::Integer(-2147483648));
+    assert_eq!(saturating("-2147483648 / -1"), Value::Integer(2147483647));
+    assert_eq!(saturating("(-3) ** 21"), Value::Integer(-2147483648));
+    assert_eq!(saturating("-3 ** 21"), Value::Integer(-2147483647));
+    assert_eq!(saturating("3 << 30"), Value::Integer(2147483647));
+    assert_eq!(saturating("-1 << 40"), Value::Integer(-2147483648));
+    assert_eq!(saturating("0 << 40"), Value::Integer(0));
+    assert_eq!(saturating("-5 >> 40"), Value::Integer(-1));
+    assert_eq!(saturating("-(-2147483648)"), Value::Integer(2147483647));
+    assert_eq!(saturating("{ let a = -2147483648; a--; a }"), Value::Integer(-2147483648));
+}
+
+#[test]
+fn overflow_defaults_to_checked() {
+    assert_eq!(Overflow::default(), Overflow::Checked);
+}
+
+#[test]
+fn run_main() {
+    assert_eq!(run_ok("fn main() { 1 + 2 }"), Value::Integer(3));
+    assert_eq!(run_ok("fn main() {}"), Value::Unit);
+}
+
+#[test]
+fn run_variables_and_assignments() {
+    let program = "
+        fn main() {
+            let a = 1;
+            let b;
+            b = a + 1;
+            a += 10;
+            a *= b;
+            {
+                let a = 100;
+                b = a;
+            }
+            a + b
+        }";
+    assert_eq!(run_ok(program), Value::Integer(122));
+}
+
+#[test]
+fn run_increment_and_decrement() {
+    let program = "
+        fn main() {
+            let a = 5;
+            let b = a++;
+            let c = ++a;
+            let d = a--;
+            let e = --a;
+            a * 10000 + b * 1000 + c * 100 + d * 10 + e
+        }";
+    assert_eq!(run_ok(program), Value::Integer(55775));
+}
+
+#[test]
+fn run_loops() {
+    let program = "
+        fn main() {
+            let sum = 0;
+            let i = 0;
+            while i < 10 {
+                i++;
+                if i % 2 == 0 { continue; }
+                sum += i;
+            }
+            loop {
+                if sum > 100 { break; }
+                sum *= 2;
+            }
+            sum
+        }";
+    assert_eq!(run_ok(program), Value::Integer(200));
+}
+
+#[test]
+fn run_functions() {
+    let program = "
+        fn fib(n) {
+            if n < 2 { return n; }
+            fib(n - 1) + fib(n - 2)
+        }
+        fn main() { fib(15) }";
+    assert_eq!(run_ok(program), Value::Integer(610));
+}
+
+#[test]
+fn run_functions_do_not_share_variables() {
+    let program = "
+        fn get() { a }
+        fn main() { let a = 1; get() }";
+    assert_eq!(run_err(program).0, "Cannot find variable 'a'");
+}
+
+#[test]
+fn run_return_from_loop() {
+    let program = "
+        fn find(limit) {
+            let i = 0;
+            loop {
+                while 1 < 2 {
+                    if i * i > limit { return i; }
+                    i++;
+                }
+            }
+        }
+        fn main() { find(50) }";
+    assert_eq!(run_ok(program), Value::Integer(8));
+}
+
+#[test]
+fn run_recursion_limit() {
+    let program = "
+        fn count(n) { if n > 0 { count(n - 1) + 1 } else { 0 } }
+        fn main() { count(999) }";
+    assert_eq!(run_ok(program), Value::Integer(999));
+
+    let program = "
+        fn count(n) { if n > 0 { count(n - 1) + 1 } else { 0 } }
+        fn main() { count(1000) }";
+    assert_eq!(
+        run_err(program),
+        ("Recursion limit exceeded, calls may nest at most 1000 deep".to_string(), (34, 46))
+    );
+
+    let (message, _) = run_err("fn main() { main() }");
+    assert_eq!(message, "Recursion limit exceeded, calls may nest at most 1000 deep");
+}
+
+#[test]
+fn run_errors() {
+    assert_eq!(run_err("fn f() {}"), ("No 'main' function found".to_string(), (0, 0)));
+    assert_eq!(
+        run_err("fn main(a, b) {}"),
+        ("The 'main' function must not take parameters".to_string(), (8, 12))
+    );
+    assert_eq!(
+        run_err("fn f(a) { a } fn main() { f(1, 2) }"),
+        ("Function 'f' takes 1 argument(s) but 2 were given".to_string(), (26, 33))
+    );
+    assert_eq!(
+        run_err("fn main() { g() }"),
+        ("Cannot find function 'g'".to_string(), (12, 13))
+    );
+    assert_eq!(
+        run_err("fn main() { let a = 1; a[0] }"),
+        ("Cannot index into a value of type integer".to_string(), (23, 27))
+    );
+}
+
+#[test]
+fn value_display() {
+    assert_eq!(Value::Integer(-3).to_string(), "-3");
+    assert_eq!(Value::String("text".to_string()).to_string(), "text");
+    assert_eq!(Value::Boolean(true).to_string(), "true");
+    assert_eq!(Value::Unit.to_string(), "()");
+}
+
+fn eval_ok(input: &str) -> Value {
+    eval(input, Overflow::Checked).expect("Runtime error")
+}
+
+fn eval_err(input: &str) -> (String, (usize, usize)) {
+    let error = eval(input, Overflow::Checked).expect_err("Expected a runtime error");
+    (error.message().to_string(), (error.span().start.index, error.span().end.index))
+}
+
+fn eval(input: &str, overflow: Overflow) -> EvalResult<Value> {
+    let (tokens, errors) = lexer::lex(input.as_bytes());
+    assert!(errors.is_empty(), "Lex errors {:?}", errors);
+    let (expr, errors) = parser::parse_expression(&tokens);
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    interpreter::eval(&expr, overflow)
+}
+
+fn run_ok(input: &str) -> Value {
+    run(input).expect("Runtime error")
+}
+
+fn run_err(input: &str) -> (String, (usize, usize)) {
+    let error = run(input).expect_err("Expected a runtime error");
+    (error.message().to_string(), (error.span().start.index, error.span().end.index))
+}
+
+fn run(input: &str) -> EvalResult<Value> {
+    let (tokens, errors) = lexer::lex(input.as_bytes());
+    assert!(errors.is_empty(), "Lex errors {:?}", errors);
+    let (module, errors) = parser::program(&tokens);
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    interpreter::run(&module.items, FileId::default(), Overflow::Checked)
+}
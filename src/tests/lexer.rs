@@ -1,5 +1,6 @@
 use simplelang::*;
 use simplelang::lexer;
+use simplelang::source_map::*;
 
 #[test]
 fn empty() {
@@ -9,18 +10,76 @@ fn empty() {
 #[test]
 fn tokens() {
     assert_token("123", TokenKind::Integer);
-    assert_token("\"Hello, World!\"", TokenKind::String);
+    assert_tokens(
+        "\"Hello, World!\"",
+        vec![string_token(0, "\"Hello, World!\"", "Hello, World!")],
+    );
     assert_token("hello", TokenKind::Identifier);
     assert_token("fn", TokenKind::FnKeyword);
     assert_token("fnabc", TokenKind::Identifier);
     assert_token("abcfn", TokenKind::Identifier);
 }
 
+#[test]
+fn keyword_tokens() {
+    assert_token("let", TokenKind::LetKeyword);
+    assert_token("if", TokenKind::IfKeyword);
+    assert_token("else", TokenKind::ElseKeyword);
+    assert_token("while", TokenKind::WhileKeyword);
+    assert_token("loop", TokenKind::LoopKeyword);
+    assert_token("break", TokenKind::BreakKeyword);
+    assert_token("continue", TokenKind::ContinueKeyword);
+    assert_token("return", TokenKind::ReturnKeyword);
+    assert_token("letter", TokenKind::Identifier);
+    assert_token("_if", TokenKind::Identifier);
+}
+
 #[test]
 fn op_tokens() {
     assert_token("+", TokenKind::Plus);
     assert_token("++", TokenKind::PlusPlus);
     assert_token("+=", TokenKind::PlusEqual);
+    assert_token("-", TokenKind::Minus);
+    assert_token("--", TokenKind::MinusMinus);
+    assert_token("-=", TokenKind::MinusEqual);
+    assert_token("*", TokenKind::Asterisk);
+    assert_token("*=", TokenKind::AsteriskEqual);
+    assert_token("**", TokenKind::AsteriskAsterisk);
+    assert_token("**=", TokenKind::AsteriskAsteriskEqual);
+    assert_token("/=", TokenKind::SlashEqual);
+    assert_token("%=", TokenKind::PercentEqual);
+    assert_token("=", TokenKind::Equal);
+    assert_token("&=", TokenKind::AmpersandEqual);
+    assert_token("|=", TokenKind::PipeEqual);
+    assert_token("^=", TokenKind::CaretEqual);
+    assert_token("<<=", TokenKind::LessLessEqual);
+    assert_token(">>=", TokenKind::GreaterGreaterEqual);
+    assert_token("/", TokenKind::Slash);
+    assert_token("%", TokenKind::Percent);
+    assert_token("==", TokenKind::EqualEqual);
+    assert_token("!=", TokenKind::ExclamationEqual);
+    assert_token("<", TokenKind::Less);
+    assert_token("<=", TokenKind::LessEqual);
+    assert_token(">", TokenKind::Greater);
+    assert_token(">=", TokenKind::GreaterEqual);
+    assert_token("!", TokenKind::Exclamation);
+    assert_token("&&", TokenKind::AmpersandAmpersand);
+    assert_token("||", TokenKind::PipePipe);
+    assert_token("&", TokenKind::Ampersand);
+    assert_token("|", TokenKind::Pipe);
+    assert_token("^", TokenKind::Caret);
+    assert_token("~", TokenKind::Tilde);
+    assert_token("<<", TokenKind::LessLess);
+    assert_token(">>", TokenKind::GreaterGreater);
+    assert_token("(", TokenKind::LParen);
+    assert_token(")", TokenKind::RParen);
+    assert_token("{", TokenKind::LBrace);
+    assert_token("}", TokenKind::RBrace);
+    assert_token("[", TokenKind::LBracket);
+    assert_token("]", TokenKind::RBracket);
+    assert_token(",", TokenKind::Comma);
+    assert_token(".", TokenKind::Dot);
+    assert_token(";", TokenKind::Semicolon);
     assert_tokens(
         "+++",
         vec![
@@ -28,6 +87,31 @@ fn op_tokens() {
             new_token(2, "+", TokenKind::Plus),
         ],
     );
+    assert_tokens(
+        "<<<&&&",
+        vec![
+            new_token(0, "<<", TokenKind::LessLess),
+            new_token(2, "<", TokenKind::Less),
+            new_token(3, "&&", TokenKind::AmpersandAmpersand),
+            new_token(5, "&", TokenKind::Ampersand),
+        ],
+    );
+    assert_tokens(
+        "===<<",
+        vec![
+            new_token(0, "==", TokenKind::EqualEqual),
+            new_token(2, "=", TokenKind::Equal),
+            new_token(3, "<<", TokenKind::LessLess),
+        ],
+    );
+}
+
+#[test]
+fn token_kind_names() {
+    assert_eq!(TokenKind::Integer.to_string(), "integer");
+    assert_eq!(TokenKind::PlusEqual.to_string(), "'+='");
+    assert_eq!(TokenKind::FnKeyword.to_string(), "'fn'");
+    assert_eq!(TokenKind::EndOfInput.to_string(), "end of input");
 }
 
 #[test]
@@ -43,11 +127,437 @@ fn whitespace() {
     );
 }
 
+#[test]
+fn positions_across_lines() {
+    let tokens = lex_ok("12\n  34\r\nabc");
+
+    let positions: Vec<_> = tokens.iter().map(|t| (t.start, t.end)).collect();
+    assert_eq!(
+        positions,
+        vec![
+            (pos(0, 0, 0), pos(2, 0, 2)),
+            (pos(5, 1, 2), pos(7, 1, 4)),
+            (pos(9, 2, 0), pos(12, 2, 3)),
+        ]
+    );
+}
+
+#[test]
+fn positions_count_bytes() {
+    let tokens = lex_ok("\"\u{e4}\" 1");
+
+    assert_eq!(tokens[0].end, pos(4, 0, 3));
+    assert_eq!(tokens[1].start, pos(5, 0, 4));
+}
+
+#[test]
+fn positions_carry_file() {
+    let mut source_map = SourceMap::new();
+    source_map.add_file("a.sl".to_string(), "1".to_string());
+    let b = source_map.add_file("b.sl".to_string(), "2".to_string());
+
+    let (tokens, errors) = lexer::lex_file(source_map.file(b));
+    assert!(errors.is_empty());
+    assert_eq!(tokens[0].start.file, b);
+    assert_eq!(tokens[0].end.file, b);
+}
+
+#[test]
+fn end_of_input() {
+    let (tokens, _) = lexer::lex("".as_bytes());
+    assert_eq!(tokens, vec![new_token(0, "", TokenKind::EndOfInput)]);
+
+    let (tokens, _) = lexer::lex("1 +\n\n".as_bytes());
+    let eof = tokens.last().unwrap();
+    assert_eq!(eof.kind, TokenKind::EndOfInput);
+    assert_eq!((eof.start, eof.end), (pos(3, 0, 3), pos(3, 0, 3)));
+}
+
+#[test]
+fn line_comments() {
+    assert_tokens("// only a comment", Vec::<Token>::new());
+    assert_tokens("1 // one\n// two", vec![new_token(0, "1", TokenKind::Integer)]);
+
+    let tokens = lex_ok("a // b \"c\" /* d\n/ e //= f\n");
+    let texts: Vec<_> = tokens.iter().map(|t| (&t.text[..], t.start.line)).collect();
+    assert_eq!(texts, vec![("a", 0), ("/", 1), ("e", 1)]);
+}
+
+#[test]
+fn doc_comments() {
+    let tokens = lex_ok("/// Outer doc.\n//! Inner doc.\r\n///\n//// plain\n// plain\nx");
+
+    let kinds: Vec<_> = tokens.iter().map(|t| (t.kind, &t.text[..])).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (TokenKind::DocComment, "/// Outer doc."),
+            (TokenKind::InnerDocComment, "//! Inner doc."),
+            (TokenKind::DocComment, "///"),
+            (TokenKind::Identifier, "x"),
+        ]
+    );
+
+    let values: Vec<_> = tokens.iter().map(|t| t.value.as_ref().map(|v| &v[..])).collect();
+    assert_eq!(values, vec![Some(" Outer doc."), Some(" Inner doc."), Some(""), None]);
+
+    assert_eq!((tokens[1].start, tokens[1].end), (pos(15, 1, 0), pos(29, 1, 14)));
+    assert_eq!(tokens[2].start, pos(31, 2, 0));
+}
+
+#[test]
+fn block_comments() {
+    assert_tokens(
+        "1 /* a */ 2",
+        vec![
+            new_token(0, "1", TokenKind::Integer),
+            new_token(10, "2", TokenKind::Integer),
+        ],
+    );
+    assert_tokens("/**/ /***/ /* * / */", Vec::<Token>::new());
+
+    let tokens = lex_ok("a /* first\n /* nested */\n*/ b");
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[1].text, "b");
+    assert_eq!(tokens[1].start, pos(28, 2, 3));
+
+    // comments are part of the string
+    assert_eq!(string_value("\"/* a */ // b\""), "/* a */ // b");
+
+    // the '/' and '*' of an operator do not start a comment
+    let kinds: Vec<_> = lex_ok("a / *b */ c").iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Identifier,
+            TokenKind::Slash,
+            TokenKind::Asterisk,
+            TokenKind::Identifier,
+            TokenKind::Asterisk,
+            TokenKind::Slash,
+            TokenKind::Identifier,
+        ]
+    );
+}
+
+#[test]
+fn comments_in_interpolations() {
+    let tokens = lex_ok("\"a${ b /* } */ // }\n}c\"");
+    let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![TokenKind::StringStart, TokenKind::Identifier, TokenKind::StringEnd]
+    );
+}
+
+#[test]
+fn unterminated_block_comments() {
+    let message = "Unterminated block comment, expected '*/' to close the '/*' opened here";
+
+    let (tokens, errors) = lexer::lex("1\n  /* a /* b */\n c".as_bytes());
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0].text, "1");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message(), message);
+    assert_eq!((errors[0].start(), errors[0].end()), (pos(4, 1, 2), pos(6, 1, 4)));
+    assert_eq!(errors[0].code().as_str(), "E0005");
+    let related = errors[0].related().expect("Expected the end of the input");
+    assert_eq!((related.start, related.end), (pos(19, 2, 2), pos(19, 2, 2)));
+    assert_eq!(related.message, "the input ends before the comment is closed");
+
+    assert_eq!(string_errors("/*/"), vec![(0, 2, message.to_string())]);
+}
+
+#[test]
+fn error_recovery() {
+    let (tokens, errors) = lexer::lex("1 $ 2 \"abc".as_bytes());
+
+    assert_eq!(
+        tokens,
+        vec![
+            new_token(0, "1", TokenKind::Integer),
+            new_token(2, "$", TokenKind::Error),
+            new_token(4, "2", TokenKind::Integer),
+            new_token(6, "\"abc", TokenKind::Error),
+            Token {
+                unterminated: true,
+                ..new_token(10, "", TokenKind::EndOfInput)
+            },
+        ]
+    );
+
+    let messages: Vec<_> = errors.iter().map(|e| (e.start().index, e.message())).collect();
+    assert_eq!(messages, vec![(2, "Unexpected symbol '$'"), (6, "Unterminated string literal")]);
+    assert_eq!(errors[1].end().index, 7);
+}
+
+#[test]
+fn string_escapes() {
+    assert_eq!(string_value(r#""a\nb""#), "a\nb");
+    assert_eq!(string_value(r#""\ta\t""#), "\ta\t");
+    assert_eq!(string_value(r#""\\ \"quoted\" \\""#), "\\ \"quoted\" \\");
+    assert_eq!(string_value(r#""\u{41}\u{e4}\u{1F600}\u{0}""#), "A\u{e4}\u{1F600}\u{0}");
+    assert_eq!(string_value(r#""\u{10FFFF}""#), "\u{10FFFF}");
+    assert_eq!(string_value("\"\""), "");
+
+    let tokens = lex_ok(r#""\"" 1"#);
+    assert_eq!(tokens[0].text, r#""\"""#);
+    assert_eq!(tokens[1].text, "1");
+}
+
+#[test]
+fn multi_line_strings() {
+    let tokens = lex_ok("\"first\n  second\r\n\" x");
+
+    assert_eq!(tokens[0].value, Some("first\n  second\r\n".to_string()));
+    assert_eq!((tokens[0].start, tokens[0].end), (pos(0, 0, 0), pos(18, 2, 1)));
+    assert_eq!(tokens[1].start, pos(19, 2, 2));
+}
+
+#[test]
+fn invalid_string_escapes() {
+    let unicode = "Invalid unicode escape, expected '\\u{' followed by 1 to 6 hex digits and '}'";
+
+    assert_eq!(string_errors(r#""a\qb""#), vec![(2, 4, "Unknown escape sequence '\\q'".to_string())]);
+    assert_eq!(string_errors(r#""\u41""#), vec![(1, 3, unicode.to_string())]);
+    assert_eq!(string_errors(r#""\u{}""#), vec![(1, 4, unicode.to_string())]);
+    assert_eq!(string_errors(r#""\u{41""#), vec![(1, 6, unicode.to_string())]);
+    assert_eq!(string_errors(r#""\u{1234567}""#), vec![(1, 11, unicode.to_string())]);
+    assert_eq!(
+        string_errors(r#""\u{D800}""#),
+        vec![(1, 9, "Invalid unicode escape, U+D800 is not a valid character".to_string())]
+    );
+    assert_eq!(
+        string_errors(r#""\u{110000}""#),
+        vec![(1, 11, "Invalid unicode escape, U+110000 is not a valid character".to_string())]
+    );
+    assert_eq!(
+        string_errors(r#""\a \b""#),
+        vec![
+            (1, 3, "Unknown escape sequence '\\a'".to_string()),
+            (4, 6, "Unknown escape sequence '\\b'".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn invalid_string_escapes_recover() {
+    let (tokens, errors) = lexer::lex(r#""\x" + "ok""#.as_bytes());
+
+    let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![TokenKind::Error, TokenKind::Plus, TokenKind::String, TokenKind::EndOfInput]
+    );
+    assert_eq!(tokens[0].text, r#""\x""#);
+    assert_eq!(tokens[0].value, None);
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn invalid_escapes_in_interpolated_strings_keep_their_kind() {
+    let (tokens, errors) = lexer::lex(r#""a\xb${c}d\qe""#.as_bytes());
+
+    let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::StringStart,
+            TokenKind::Identifier,
+            TokenKind::StringEnd,
+            TokenKind::EndOfInput,
+        ]
+    );
+    assert_eq!(tokens[0].value, Some("ab".to_string()));
+    assert_eq!(tokens[2].value, Some("de".to_string()));
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn unterminated_strings() {
+    let (tokens, errors) = lexer::lex("1 \"abc\ndef".as_bytes());
+    assert_eq!(tokens[1].kind, TokenKind::Error);
+    assert_eq!(tokens[1].text, "\"abc\ndef");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message(), "Unterminated string literal");
+    assert_eq!((errors[0].start(), errors[0].end()), (pos(2, 0, 2), pos(3, 0, 3)));
+
+    // an escaped quote does not end the string
+    let (tokens, errors) = lexer::lex(r#""abc\""#.as_bytes());
+    assert_eq!(tokens[0].kind, TokenKind::Error);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message(), "Unterminated string literal");
+
+    let (_, errors) = lexer::lex(r#""abc\"#.as_bytes());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message(), "Unterminated string literal");
+}
+
+#[test]
+fn raw_strings() {
+    assert_eq!(string_value(r#"r"C:\path\new""#), r"C:\path\new");
+    assert_eq!(string_value(r###"r#"contains "quotes""#"###), r#"contains "quotes""#);
+    assert_eq!(string_value(r###"r##"ends with "# inside"##"###), r##"ends with "# inside"##);
+    assert_eq!(string_value("r\"\""), "");
+    assert_eq!(string_value("r\"two\nlines\""), "two\nlines");
+
+    let tokens = lex_ok(r###"r#"a"# r"b" r"###);
+    assert_eq!(
+        tokens,
+        vec![
+            string_token(0, r###"r#"a"#"###, "a"),
+            string_token(7, r#"r"b""#, "b"),
+            new_token(12, "r", TokenKind::Identifier),
+        ]
+    );
+
+    // without a quote, `r` and `#` do not start a raw string
+    let (tokens, _) = lexer::lex("r#".as_bytes());
+    assert_eq!(tokens[0], new_token(0, "r", TokenKind::Identifier));
+    assert_eq!(tokens[1].kind, TokenKind::Error);
+}
+
+#[test]
+fn unterminated_raw_strings() {
+    let (tokens, errors) = lexer::lex("1\n  r##\"abc\"# x".as_bytes());
+
+    assert_eq!(tokens[1].kind, TokenKind::Error);
+    assert_eq!(tokens[1].text, "r##\"abc\"# x");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message(),
+        "Unterminated raw string literal, expected '\"##' to close the 'r##\"' opened here"
+    );
+    assert_eq!((errors[0].start(), errors[0].end()), (pos(4, 1, 2), pos(8, 1, 6)));
+
+    assert_eq!(
+        string_errors("r\"abc"),
+        vec![
+            (
+                0,
+                2,
+                "Unterminated raw string literal, expected '\"' to close the 'r\"' opened here"
+                    .to_string(),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn interpolated_strings() {
+    let tokens = lex_ok(r#""Hello, ${name}! You have ${count + 1} items""#);
+
+    let kinds: Vec<_> = tokens.iter().map(|t| (t.kind, &t.text[..])).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (TokenKind::StringStart, r#""Hello, ${"#),
+            (TokenKind::Identifier, "name"),
+            (TokenKind::StringMiddle, "}! You have ${"),
+            (TokenKind::Identifier, "count"),
+            (TokenKind::Plus, "+"),
+            (TokenKind::Integer, "1"),
+            (TokenKind::StringEnd, r#"} items""#),
+        ]
+    );
+
+    let values: Vec<_> = tokens.iter().map(|t| t.value.as_ref().map(|v| &v[..])).collect();
+    assert_eq!(
+        values,
+        vec![Some("Hello, "), None, Some("! You have "), None, None, None, Some(" items")]
+    );
+
+    let spans: Vec<_> = tokens.iter().map(|t| (t.start.index, t.end.index)).collect();
+    assert_eq!(spans, vec![(0, 10), (10, 14), (14, 28), (28, 33), (34, 35), (36, 37), (37, 45)]);
+}
+
+#[test]
+fn interpolated_strings_nest() {
+    let tokens = lex_ok(r#""a${ { "}" } + "b${ "${c}" }d" }e" x"#);
+
+    let kinds: Vec<_> = tokens.iter().map(|t| (t.kind, &t.text[..])).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (TokenKind::StringStart, r#""a${"#),
+            (TokenKind::LBrace, "{"),
+            (TokenKind::String, r#""}""#),
+            (TokenKind::RBrace, "}"),
+            (TokenKind::Plus, "+"),
+            (TokenKind::StringStart, r#""b${"#),
+            (TokenKind::StringStart, r#""${"#),
+            (TokenKind::Identifier, "c"),
+            (TokenKind::StringEnd, r#"}""#),
+            (TokenKind::StringEnd, r#"}d""#),
+            (TokenKind::StringEnd, r#"}e""#),
+            (TokenKind::Identifier, "x"),
+        ]
+    );
+}
+
+#[test]
+fn interpolation_escapes() {
+    assert_eq!(string_value(r#""\${a} $a {a}""#), "${a} $a {a}");
+
+    let tokens = lex_ok(r#""\t${a}\n""#);
+    assert_eq!(tokens[0].value, Some("\t".to_string()));
+    assert_eq!(tokens[2].value, Some("\n".to_string()));
+
+    // raw strings are not interpolated
+    assert_eq!(string_value(r#"r"${a}""#), "${a}");
+}
+
+#[test]
+fn unterminated_interpolated_strings() {
+    let (tokens, errors) = lexer::lex("x = \"a ${ b".as_bytes());
+    assert_eq!(tokens[2].kind, TokenKind::StringStart);
+    assert_eq!(
+        string_errors("x = \"a ${ b"),
+        vec![(4, 5, "Unterminated string literal".to_string())]
+    );
+    assert_eq!(errors.len(), 1);
+
+    // the string continues after the interpolation
+    let (tokens, _) = lexer::lex("\"a ${ b } c".as_bytes());
+    assert_eq!(tokens[2].kind, TokenKind::Error);
+    assert_eq!(
+        string_errors("\"a ${ b } c"),
+        vec![(0, 1, "Unterminated string literal".to_string())]
+    );
+
+    // both the inner and the outer string are unterminated
+    assert_eq!(
+        string_errors("\"a ${ \"b ${ c"),
+        vec![
+            (6, 7, "Unterminated string literal".to_string()),
+            (0, 1, "Unterminated string literal".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn error_recovery_reports_every_error() {
+    let (tokens, errors) = lexer::lex("$\n#\n@".as_bytes());
+
+    assert_eq!(tokens.len(), 4);
+    assert!(tokens[..3].iter().all(|t| t.kind == TokenKind::Error));
+
+    let lines: Vec<_> = errors.iter().map(|e| e.start().line).collect();
+    assert_eq!(lines, vec![0, 1, 2]);
+}
+
 fn assert_tokens(input: &str, expected: Vec<Token>) {
-    let actual = lexer::lex(input.as_bytes()).unwrap();
+    let actual = lex_ok(input);
     assert_eq!(expected, actual);
 }
 
+fn lex_ok(input: &str) -> Vec<Token> {
+    let (mut tokens, errors) = lexer::lex(input.as_bytes());
+    assert!(errors.is_empty(), "Unexpected lex errors {:?}", errors);
+    assert_eq!(tokens.pop().map(|t| t.kind), Some(TokenKind::EndOfInput));
+    tokens
+}
+
 fn assert_token(input: &str, kind: TokenKind) {
     let token = new_token(0, input, kind);
     assert_tokens(input, vec![token]);
@@ -55,9 +565,42 @@ fn assert_token(input: &str, kind: TokenKind) {
 
 fn new_token(start: usize, text: &str, kind: TokenKind) -> Token {
     Token {
-        start: TextPosition { index: start },
-        end: TextPosition { index: start + text.len() },
+        start: pos(start, 0, start),
+        end: pos(start + text.len(), 0, start + text.len()),
         text: text.to_owned(),
         kind: kind,
+        value: None,
+        unterminated: false,
+    }
+}
+
+fn string_token(start: usize, text: &str, value: &str) -> Token {
+    Token {
+        value: Some(value.to_owned()),
+        ..new_token(start, text, TokenKind::String)
+    }
+}
+
+fn string_value(input: &str) -> String {
+    let tokens = lex_ok(input);
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].kind, TokenKind::String);
+    tokens[0].value.clone().unwrap()
+}
+
+fn string_errors(input: &str) -> Vec<(usize, usize, String)> {
+    let (_, errors) = lexer::lex(input.as_bytes());
+    errors
+        .iter()
+        .map(|e| (e.start().index, e.end().index, e.message().to_string()))
+        .collect()
+}
+
+fn pos(index: usize, line: usize, column: usize) -> TextPosition {
+    TextPosition {
+        file: FileId::default(),
+        index,
+        line,
+        column,
     }
 }
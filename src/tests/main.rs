@@ -0,0 +1,15 @@
+use {parse_args, Command};
+
+#[test]
+fn doc_rejects_json_messages() {
+    let args = |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+
+    let options = parse_args(&args(&["doc", "--message-format=human", "a.sl"])).unwrap();
+    assert_eq!(options.command, Command::Doc);
+    assert_eq!(options.filenames, vec!["a.sl".to_string()]);
+
+    assert_eq!(
+        parse_args(&args(&["doc", "--message-format=json", "a.sl"])).unwrap_err(),
+        "'doc' prints Markdown and cannot be used with '--message-format=json'"
+    );
+}
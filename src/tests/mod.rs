@@ -1,3 +1,9 @@
 mod lexer;
 mod parser;
 mod indexed_slice;
+mod source_map;
+mod diagnostics;
+mod semantic;
+mod interpreter;
+mod docs;
+mod main;
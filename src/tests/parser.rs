@@ -1,22 +1,173 @@
 use simplelang::*;
 use simplelang::ast::*;
+use simplelang::diagnostics::Severity;
 
 #[test]
 fn expr_integer() {
-    assert_eq!(parse_expr("123"), ExpressionNode::Integer(123));
+    assert_eq!(parse_expr("123"), integer(123));
+}
+
+#[test]
+fn expr_integer_bounds() {
+    assert_eq!(parse_expr("2147483647"), integer(i32::MAX));
+    assert_eq!(parse_expr("-2147483648"), integer(i32::MIN));
+    assert_eq!(span_of(&parse_expr("-2147483648")), (0, 11));
+    assert_eq!(
+        parse_expr("- -2147483648"),
+        unary(UnaryOperator::Negate, integer(i32::MIN))
+    );
+    assert_eq!(
+        parse_expr("-2147483647"),
+        unary(UnaryOperator::Negate, integer(i32::MAX))
+    );
+}
+
+#[test]
+fn expr_integer_out_of_range() {
+    let message = "Integer literal out of range, integers must be between -2147483648 and 2147483647";
+
+    let (tokens, _) = lexer::lex("1 + 99999999999999999999 * 2".as_bytes());
+    let (expr, errors) = parser::parse_expression(&tokens);
+    assert_eq!(
+        expr,
+        binary(
+            BinaryOperator::Add,
+            integer(1),
+            binary(BinaryOperator::Mul, error(), integer(2)),
+        )
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message(), message);
+    assert_eq!((errors[0].start().index, errors[0].end().index), (4, 24));
+
+    assert_eq!(parse_with_errors("2147483648"), (error(), vec![message.to_string()]));
+    assert_eq!(
+        parse_with_errors("~2147483648"),
+        (unary(UnaryOperator::BitNot, error()), vec![message.to_string()])
+    );
 }
 
 #[test]
 fn expr_string() {
+    assert_eq!(parse_expr("\"hello\""), string("hello"));
+    assert_eq!(parse_expr(r#""a\"b\"\n\u{e4}""#), string("a\"b\"\n\u{e4}"));
+    assert_eq!(parse_expr("\"two\nlines\""), string("two\nlines"));
+    assert_eq!(parse_expr(r###"r#"raw \n "quoted""#"###), string(r#"raw \n "quoted""#));
+}
+
+#[test]
+fn expr_interpolated_string() {
+    assert_eq!(
+        parse_expr(r#""Hello, ${name}! You have ${count + 1} items""#),
+        interpolated(vec![
+            text("Hello, "),
+            part(variable("name")),
+            text("! You have "),
+            part(binary(BinaryOperator::Add, variable("count"), integer(1))),
+            text(" items"),
+        ])
+    );
+    assert_eq!(parse_expr(r#""${a}${b}""#), interpolated(vec![part(variable("a")), part(variable("b"))]));
+    assert_eq!(
+        parse_expr(r#""${ {"}"} }" + 1"#),
+        binary(
+            BinaryOperator::Add,
+            interpolated(vec![part(block_expression(block_with_value(vec![], string("}"))))]),
+            integer(1),
+        )
+    );
+    assert_eq!(
+        parse_expr(r#""a${ "b${c}" }""#),
+        interpolated(vec![
+            text("a"),
+            part(interpolated(vec![text("b"), part(variable("c"))])),
+        ])
+    );
+}
+
+#[test]
+fn expr_interpolated_string_spans() {
+    let expr = parse_expr(r#"x + "a${ f(1) }b""#);
+
+    let string = match *expr.kind() {
+        ExpressionKind::Binary(_, _, ref rhs) => rhs,
+        _ => panic!("Expected a binary expression"),
+    };
+    assert_eq!(span_of(string), (4, 17));
+
+    let call = match *string.kind() {
+        ExpressionKind::Interpolated(ref parts) => {
+            match parts[1] {
+                StringPart::Expression(ref call) => call,
+                _ => panic!("Expected an interpolation"),
+            }
+        }
+        _ => panic!("Expected an interpolated string"),
+    };
+    assert_eq!(span_of(call), (9, 13));
+}
+
+#[test]
+fn expr_interpolated_string_errors() {
+    let (expr, errors) = parse_with_errors(r#""a${1 2 "${x}"}b${c}" + d"#);
+    assert_eq!(
+        expr,
+        binary(
+            BinaryOperator::Add,
+            interpolated(vec![text("a"), part(integer(1)), text("b"), part(variable("c"))]),
+            variable("d"),
+        )
+    );
+    assert_eq!(errors, vec![expected_continuation(&[TokenKind::StringEnd], "'2'")]);
+
+    let (_, errors) = parse_with_errors(r#""a${}""#);
+    assert_eq!(errors, vec![expected_operand("'}\"'")]);
+
+    // the lexer reports the unterminated string, the parser does not add to it
+    let (_, errors) = parse_with_errors("\"a${1} b");
+    assert_eq!(errors, vec!["Unterminated string literal"]);
+
+    let (_, errors) = parse_with_errors("\"a${b");
+    assert_eq!(errors, vec!["Unterminated string literal"]);
+
+    // other lexer errors do not end the string
+    let (expr, errors) = parse_with_errors(r#""a ${ x @ } b""#);
+    assert_eq!(expr, interpolated(vec![text("a "), part(variable("x")), text(" b")]));
+    assert_eq!(errors, vec!["Unexpected symbol '@'"]);
+}
+
+#[test]
+fn expr_variable() {
+    assert_eq!(parse_expr("abc"), variable("abc"));
+    assert_eq!(parse_expr("_x1"), variable("_x1"));
+}
+
+#[test]
+fn expr_variable_in_binary() {
+    assert_eq!(
+        parse_expr("a + b * 2"),
+        binary(
+            BinaryOperator::Add,
+            variable("a"),
+            binary(BinaryOperator::Mul, variable("b"), integer(2)),
+        )
+    );
+}
+
+#[test]
+fn expr_variable_unary() {
     assert_eq!(
-        parse_expr("\"hello\""),
-        ExpressionNode::String("hello".to_string())
+        parse_expr("-x++"),
+        unary(
+            UnaryOperator::Negate,
+            unary(UnaryOperator::PostInc, variable("x")),
+        )
     );
 }
 
 #[test]
 fn expr_parenthesis() {
-    assert_eq!(parse_expr("(456)"), ExpressionNode::Integer(456));
+    assert_eq!(parse_expr("(456)"), integer(456));
 }
 
 #[test]
@@ -24,11 +175,7 @@ fn expr_binary() {
 
     assert_eq!(
         parse_expr("12 + 34"),
-        ExpressionNode::Binary(
-            BinaryOperator::Add,
-            Box::new(ExpressionNode::Integer(12)),
-            Box::new(ExpressionNode::Integer(34)),
-        )
+        binary(BinaryOperator::Add, integer(12), integer(34))
     );
 }
 
@@ -37,27 +184,19 @@ fn expr_binary_precendence() {
 
     assert_eq!(
         parse_expr("12 + 34 * 56"),
-        ExpressionNode::Binary(
+        binary(
             BinaryOperator::Add,
-            Box::new(ExpressionNode::Integer(12)),
-            Box::new(ExpressionNode::Binary(
-                BinaryOperator::Mul,
-                Box::new(ExpressionNode::Integer(34)),
-                Box::new(ExpressionNode::Integer(56)),
-            )),
+            integer(12),
+            binary(BinaryOperator::Mul, integer(34), integer(56)),
         )
     );
 
     assert_eq!(
         parse_expr("12 * 34 + 56"),
-        ExpressionNode::Binary(
+        binary(
             BinaryOperator::Add,
-            Box::new(ExpressionNode::Binary(
-                BinaryOperator::Mul,
-                Box::new(ExpressionNode::Integer(12)),
-                Box::new(ExpressionNode::Integer(34)),
-            )),
-            Box::new(ExpressionNode::Integer(56)),
+            binary(BinaryOperator::Mul, integer(12), integer(34)),
+            integer(56),
         )
     );
 }
@@ -67,14 +206,10 @@ fn expr_binary_associativity() {
 
     assert_eq!(
         parse_expr("12 + 34 + 56"),
-        ExpressionNode::Binary(
+        binary(
             BinaryOperator::Add,
-            Box::new(ExpressionNode::Binary(
-                BinaryOperator::Add,
-                Box::new(ExpressionNode::Integer(12)),
-                Box::new(ExpressionNode::Integer(34)),
-            )),
-            Box::new(ExpressionNode::Integer(56)),
+            binary(BinaryOperator::Add, integer(12), integer(34)),
+            integer(56),
         )
     );
 }
@@ -83,12 +218,9 @@ fn expr_binary_associativity() {
 fn expr_unary_prefix() {
     assert_eq!(
         parse_expr("+-5"),
-        ExpressionNode::Unary(
+        unary(
             UnaryOperator::Plus,
-            Box::new(ExpressionNode::Unary(
-                UnaryOperator::Negate,
-                Box::new(ExpressionNode::Integer(5)),
-            )),
+            unary(UnaryOperator::Negate, integer(5)),
         )
     );
 }
@@ -97,29 +229,1342 @@ fn expr_unary_prefix() {
 fn expr_unary_postfix() {
     assert_eq!(
         parse_expr("5--++"),
-        ExpressionNode::Unary(
+        unary(
             UnaryOperator::PostInc,
-            Box::new(ExpressionNode::Unary(
-                UnaryOperator::PostDec,
-                Box::new(ExpressionNode::Integer(5)),
-            )),
+            unary(UnaryOperator::PostDec, integer(5)),
         )
     );
 }
 
-fn parse_expr(input: &str) -> ExpressionNode {
-    let tokens = lexer::lex(input.as_bytes()).expect(
-        "Lex error",
+#[test]
+fn recover_missing_operand() {
+    let (expr, errors) = parse_with_errors("1 + * 2");
+    assert_eq!(expr, binary(BinaryOperator::Add, integer(1), error()));
+    assert_eq!(
+        errors,
+        vec![expected_operand("'*'")]
     );
-    let (tokens, expr) = parser::expression(&tokens).expect("Parse error");
-    assert_eof(tokens);
-    expr
 }
 
-fn assert_eof(tokens: &[Token]) {
-    assert!(
-        tokens.len() == 0,
-        "Expected end of file got tokens {:?}",
-        tokens.iter().map(|t| &t.text).collect::<Vec<&String>>()
+#[test]
+fn recover_at_closing_paren() {
+    let (expr, errors) = parse_with_errors("(1 2) + 3");
+    assert_eq!(expr, binary(BinaryOperator::Add, integer(1), integer(3)));
+    assert_eq!(
+        errors,
+        vec![expected_continuation(&[TokenKind::RParen], "'2'")]
+    );
+}
+
+#[test]
+fn recover_inside_parens() {
+    let (expr, errors) = parse_with_errors("(*) * 4");
+    assert_eq!(expr, binary(BinaryOperator::Mul, error(), integer(4)));
+    assert_eq!(
+        errors,
+        vec![expected_operand("'*'")]
+    );
+}
+
+#[test]
+fn recover_reports_every_error() {
+    let (_, errors) = parse_with_errors("1 + ) 2 ) fn");
+    assert_eq!(
+        errors,
+        vec![
+            expected_operand("')'"),
+            expected_end_of_input("')'"),
+            expected_end_of_input("'fn'"),
+        ]
+    );
+}
+
+#[test]
+fn recover_from_lexer_errors() {
+    let (expr, errors) = parse_with_errors("1 + $");
+    assert_eq!(expr, binary(BinaryOperator::Add, integer(1), error()));
+    assert_eq!(errors, vec!["Unexpected symbol '$'"]);
+}
+
+#[test]
+fn lexer_errors_are_not_reported_again() {
+    let (expr, errors) = parse_with_errors("f(1 $)");
+    assert_eq!(expr, call(variable("f"), vec![integer(1)]));
+    assert_eq!(errors, vec!["Unexpected symbol '$'"]);
+
+    let inputs = ["fn main() { (1 $) }", "fn main() { 1 $ }", "fn main() { let x = 1 $; }"];
+    for input in inputs.iter() {
+        let (_, errors) = parse_program_with_errors(input);
+        assert_eq!(errors, vec!["Unexpected symbol '$'"], "{}", input);
+    }
+}
+
+#[test]
+fn unterminated_literals_at_end_of_input_are_not_reported_again() {
+    let (_, errors) = parse_program_with_errors("fn main() { \"abc");
+    assert_eq!(errors, vec!["Unterminated string literal"]);
+
+    let (_, errors) = parse_program_with_errors("fn main() { f(\"a ${ b");
+    assert_eq!(errors, vec!["Unterminated string literal"]);
+
+    let (_, errors) = parse_program_with_errors("fn main() { 1 /* abc");
+    assert_eq!(
+        errors,
+        vec!["Unterminated block comment, expected '*/' to close the '/*' opened here"]
+    );
+
+    // without an unterminated literal the missing brace is reported
+    let (_, errors) = parse_program_with_errors("fn main() { 1 $");
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn error_at_end_of_input() {
+    let (tokens, _) = lexer::lex("\n(1 +\n  2 *\n\n".as_bytes());
+    let (_, errors) = parser::parse_expression(&tokens);
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message(),
+        expected_operand("end of input")
+    );
+    assert_eq!((errors[0].start().line, errors[0].start().column), (2, 5));
+}
+
+#[test]
+fn error_missing_paren_at_end_of_input() {
+    let (expr, errors) = parse_with_errors("(1");
+    assert_eq!(expr, integer(1));
+    assert_eq!(errors, vec![expected_continuation(&[TokenKind::RParen], "end of input")]);
+}
+
+#[test]
+fn error_lists_expected_tokens() {
+    let (tokens, _) = lexer::lex("(1 2".as_bytes());
+    let (_, errors) = parser::parse_expression(&tokens);
+
+    let expected = errors[0].expected();
+    assert!(expected.contains(&TokenKind::RParen));
+    assert!(expected.contains(&TokenKind::PlusPlus));
+    assert!(expected.contains(&TokenKind::Asterisk));
+    assert!(expected.contains(&TokenKind::PipePipe));
+    assert!(expected.contains(&TokenKind::LParen));
+    assert!(!expected.contains(&TokenKind::Integer));
+    assert!(!expected.contains(&TokenKind::Identifier));
+}
+
+#[test]
+fn spans() {
+    let expr = parse_expr("-(1 + 23) * 4++");
+    assert_eq!(span_of(&expr), (0, 15));
+
+    let (lhs, rhs) = match *expr.kind() {
+        ExpressionKind::Binary(_, ref lhs, ref rhs) => (lhs, rhs),
+        ref kind => panic!("Expected binary expression, got {:?}", kind),
+    };
+    assert_eq!(span_of(lhs), (0, 9));
+    assert_eq!(span_of(rhs), (12, 15));
+
+    let parenthesized = match *lhs.kind() {
+        ExpressionKind::Unary(_, ref operand) => operand,
+        ref kind => panic!("Expected unary expression, got {:?}", kind),
+    };
+    assert_eq!(span_of(parenthesized), (1, 9));
+}
+
+#[test]
+fn spans_across_lines() {
+    let expr = parse_expr("1 +\n  2");
+    assert_eq!(expr.span().start.line, 0);
+    assert_eq!((expr.span().end.line, expr.span().end.column), (1, 3));
+}
+
+#[test]
+fn spans_of_error_nodes() {
+    let (tokens, _) = lexer::lex("1 + * * ) 2".as_bytes());
+    let (expr, _) = parser::parse_expression(&tokens);
+
+    match *expr.kind() {
+        ExpressionKind::Binary(_, _, ref rhs) => assert_eq!(span_of(rhs), (4, 7)),
+        ref kind => panic!("Expected binary expression, got {:?}", kind),
+    }
+}
+
+#[test]
+fn expr_all_binary_operators() {
+    let operators = [
+        ("**", BinaryOperator::Pow),
+        ("*", BinaryOperator::Mul),
+        ("/", BinaryOperator::Div),
+        ("%", BinaryOperator::Rem),
+        ("+", BinaryOperator::Add),
+        ("-", BinaryOperator::Sub),
+        ("<<", BinaryOperator::Shl),
+        (">>", BinaryOperator::Shr),
+        ("&", BinaryOperator::BitAnd),
+        ("^", BinaryOperator::BitXor),
+        ("|", BinaryOperator::BitOr),
+        ("==", BinaryOperator::Eq),
+        ("!=", BinaryOperator::NotEq),
+        ("<", BinaryOperator::Less),
+        ("<=", BinaryOperator::LessEq),
+        (">", BinaryOperator::Greater),
+        (">=", BinaryOperator::GreaterEq),
+        ("&&", BinaryOperator::And),
+        ("||", BinaryOperator::Or),
+    ];
+
+    for &(text, operator) in operators.iter() {
+        assert_eq!(
+            parse_expr(&format!("a {} b", text)),
+            binary(operator, variable("a"), variable("b"))
+        );
+    }
+}
+
+#[test]
+fn expr_operator_precedence() {
+    assert_eq!(
+        parse_expr("a || b && c == d | e ^ f & g << h - i % j"),
+        binary(
+            BinaryOperator::Or,
+            variable("a"),
+            binary(
+                BinaryOperator::And,
+                variable("b"),
+                binary(
+                    BinaryOperator::Eq,
+                    variable("c"),
+                    binary(
+                        BinaryOperator::BitOr,
+                        variable("d"),
+                        binary(
+                            BinaryOperator::BitXor,
+                            variable("e"),
+                            binary(
+                                BinaryOperator::BitAnd,
+                                variable("f"),
+                                binary(
+                                    BinaryOperator::Shl,
+                                    variable("g"),
+                                    binary(
+                                        BinaryOperator::Sub,
+                                        variable("h"),
+                                        binary(BinaryOperator::Rem, variable("i"), variable("j")),
+                                    ),
+                                ),
+                            ),
+                        ),
+                    ),
+                ),
+            ),
+        )
+    );
+}
+
+#[test]
+fn expr_subtraction_is_left_associative() {
+    assert_eq!(
+        parse_expr("1 - 2 - 3"),
+        binary(
+            BinaryOperator::Sub,
+            binary(BinaryOperator::Sub, integer(1), integer(2)),
+            integer(3),
+        )
+    );
+}
+
+#[test]
+fn expr_logical_and_bitwise_not() {
+    assert_eq!(
+        parse_expr("!a && ~b"),
+        binary(
+            BinaryOperator::And,
+            unary(UnaryOperator::Not, variable("a")),
+            unary(UnaryOperator::BitNot, variable("b")),
+        )
+    );
+}
+
+#[test]
+fn expr_right_associative_pow() {
+    assert_eq!(
+        parse_expr("2 ** 3 ** 4 * 5"),
+        binary(
+            BinaryOperator::Mul,
+            binary(
+                BinaryOperator::Pow,
+                integer(2),
+                binary(BinaryOperator::Pow, integer(3), integer(4)),
+            ),
+            integer(5),
+        )
+    );
+}
+
+#[test]
+fn expr_pow_binds_tighter_than_prefix_operators() {
+    assert_eq!(
+        parse_expr("-2 ** 3 ** 4 * 5"),
+        binary(
+            BinaryOperator::Mul,
+            unary(
+                UnaryOperator::Negate,
+                binary(
+                    BinaryOperator::Pow,
+                    integer(2),
+                    binary(BinaryOperator::Pow, integer(3), integer(4)),
+                ),
+            ),
+            integer(5),
+        )
+    );
+    assert_eq!(
+        parse_expr("2 ** -a ** b"),
+        binary(
+            BinaryOperator::Pow,
+            integer(2),
+            unary(
+                UnaryOperator::Negate,
+                binary(BinaryOperator::Pow, variable("a"), variable("b")),
+            ),
+        )
+    );
+    assert_eq!(
+        parse_expr("~a++ ** 2"),
+        unary(
+            UnaryOperator::BitNot,
+            binary(
+                BinaryOperator::Pow,
+                unary(UnaryOperator::PostInc, variable("a")),
+                integer(2),
+            ),
+        )
+    );
+
+    assert_eq!(
+        parse_expr("-++a ** 2"),
+        unary(
+            UnaryOperator::Negate,
+            binary(
+                BinaryOperator::Pow,
+                unary(UnaryOperator::PreInc, variable("a")),
+                integer(2),
+            ),
+        )
+    );
+
+    // the '-' is not part of the literal when it applies to the power
+    let message = "Integer literal out of range, integers must be between -2147483648 and 2147483647";
+    let (_, errors) = parse_with_errors("-2147483648 ** 0");
+    assert_eq!(errors, vec![message.to_string()]);
+}
+
+#[test]
+fn expr_comparisons_are_not_associative() {
+    let (expr, errors) = parse_with_errors("a < b < c");
+    assert_eq!(
+        expr,
+        binary(
+            BinaryOperator::Less,
+            binary(BinaryOperator::Less, variable("a"), variable("b")),
+            variable("c"),
+        )
+    );
+    assert_eq!(
+        errors,
+        vec!["Operator '<' cannot be chained with '<', use parentheses to group the operands"]
+    );
+
+    let (_, errors) = parse_with_errors("a == b + 1 >= c");
+    assert_eq!(
+        errors,
+        vec!["Operator '>=' cannot be chained with '==', use parentheses to group the operands"]
+    );
+}
+
+#[test]
+fn expr_grouped_comparisons() {
+    assert_eq!(
+        parse_expr("(a < b) == c && b < c"),
+        binary(
+            BinaryOperator::And,
+            binary(
+                BinaryOperator::Eq,
+                binary(BinaryOperator::Less, variable("a"), variable("b")),
+                variable("c"),
+            ),
+            binary(BinaryOperator::Less, variable("b"), variable("c")),
+        )
+    );
+}
+
+#[test]
+fn expr_assignment() {
+    assert_eq!(
+        parse_expr("a = b = 1 + 2"),
+        assign(
+            None,
+            variable("a"),
+            assign(
+                None,
+                variable("b"),
+                binary(BinaryOperator::Add, integer(1), integer(2)),
+            ),
+        )
+    );
+}
+
+#[test]
+fn expr_compound_assignment() {
+    let operators = [
+        ("+=", BinaryOperator::Add),
+        ("-=", BinaryOperator::Sub),
+        ("*=", BinaryOperator::Mul),
+        ("**=", BinaryOperator::Pow),
+        ("/=", BinaryOperator::Div),
+        ("%=", BinaryOperator::Rem),
+        ("&=", BinaryOperator::BitAnd),
+        ("|=", BinaryOperator::BitOr),
+        ("^=", BinaryOperator::BitXor),
+        ("<<=", BinaryOperator::Shl),
+        (">>=", BinaryOperator::Shr),
+    ];
+
+    for &(text, operator) in operators.iter() {
+        assert_eq!(
+            parse_expr(&format!("a {} b || c", text)),
+            assign(
+                Some(operator),
+                variable("a"),
+                binary(BinaryOperator::Or, variable("b"), variable("c")),
+            )
+        );
+    }
+
+    assert_eq!(
+        parse_expr("a += b -= 2"),
+        assign(
+            Some(BinaryOperator::Add),
+            variable("a"),
+            assign(Some(BinaryOperator::Sub), variable("b"), integer(2)),
+        )
+    );
+}
+
+#[test]
+fn expr_assignment_to_non_place() {
+    let (tokens, _) = lexer::lex("a + 1 = 2".as_bytes());
+    let (expr, errors) = parser::parse_expression(&tokens);
+
+    assert_eq!(
+        expr,
+        assign(
+            None,
+            binary(BinaryOperator::Add, variable("a"), integer(1)),
+            integer(2),
+        )
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message(),
+        "Invalid left-hand side of assignment, expected a variable, field or index"
+    );
+    assert_eq!((errors[0].start().index, errors[0].end().index), (0, 5));
+
+    let (_, errors) = parse_with_errors("1 += 2");
+    assert_eq!(
+        errors,
+        vec!["Invalid left-hand side of assignment, expected a variable, field or index"]
+    );
+}
+
+#[test]
+fn expr_call() {
+    assert_eq!(parse_expr("f()"), call(variable("f"), vec![]));
+    assert_eq!(
+        parse_expr("f(1, a + 2,)"),
+        call(
+            variable("f"),
+            vec![integer(1), binary(BinaryOperator::Add, variable("a"), integer(2))],
+        )
+    );
+    assert_eq!(
+        parse_expr("f(1)(2)"),
+        call(call(variable("f"), vec![integer(1)]), vec![integer(2)])
+    );
+}
+
+#[test]
+fn expr_index_and_field() {
+    assert_eq!(parse_expr("xs[i]"), index(variable("xs"), variable("i")));
+    assert_eq!(parse_expr("p.x"), field(variable("p"), "x"));
+    assert_eq!(
+        parse_expr("xs[i + 1][0]"),
+        index(
+            index(variable("xs"), binary(BinaryOperator::Add, variable("i"), integer(1))),
+            integer(0),
+        )
+    );
+}
+
+#[test]
+fn expr_postfix_chain() {
+    assert_eq!(
+        parse_expr("a.b(c)[d]"),
+        index(call(field(variable("a"), "b"), vec![variable("c")]), variable("d"))
+    );
+    assert_eq!(
+        parse_expr("-a.b++"),
+        unary(
+            UnaryOperator::Negate,
+            unary(UnaryOperator::PostInc, field(variable("a"), "b")),
+        )
+    );
+    assert_eq!(
+        parse_expr("a.b * c[0]"),
+        binary(
+            BinaryOperator::Mul,
+            field(variable("a"), "b"),
+            index(variable("c"), integer(0)),
+        )
+    );
+}
+
+#[test]
+fn expr_assignment_to_field_and_index() {
+    assert_eq!(
+        parse_expr("p.x = xs[0] += 1"),
+        assign(
+            None,
+            field(variable("p"), "x"),
+            assign(Some(BinaryOperator::Add), index(variable("xs"), integer(0)), integer(1)),
+        )
+    );
+
+    let (_, errors) = parse_with_errors("f() = 1");
+    assert_eq!(
+        errors,
+        vec!["Invalid left-hand side of assignment, expected a variable, field or index"]
     );
 }
+
+#[test]
+fn spans_of_postfix_expressions() {
+    let expr = parse_expr("a.b(c) [d]");
+    assert_eq!(span_of(&expr), (0, 10));
+
+    if let ExpressionKind::Index(ref target, _) = *expr.kind() {
+        assert_eq!(span_of(target), (0, 6));
+    } else {
+        panic!("Expected index expression, got {:?}", expr);
+    }
+}
+
+#[test]
+fn recover_inside_postfix_expressions() {
+    let (expr, errors) = parse_with_errors("f(1 2) + xs[0");
+    assert_eq!(
+        expr,
+        binary(
+            BinaryOperator::Add,
+            call(variable("f"), vec![integer(1)]),
+            index(variable("xs"), integer(0)),
+        )
+    );
+    assert_eq!(
+        errors,
+        vec![
+            expected_continuation(&[TokenKind::Comma, TokenKind::RParen], "'2'"),
+            expected_continuation(&[TokenKind::RBracket], "end of input"),
+        ]
+    );
+
+    let (expr, errors) = parse_with_errors("a.1");
+    assert_eq!(expr, error());
+    assert_eq!(errors, vec!["Expected identifier but found '1'"]);
+}
+
+#[test]
+fn program_empty() {
+    assert!(parse_program("").is_empty());
+}
+
+#[test]
+fn program_functions() {
+    let items = parse_program("fn zero() {}\nfn add(a, b) { return a + b; }\nfn trailing(a,) {}");
+    let functions = functions(&items);
+
+    assert_eq!(functions.len(), 3);
+
+    assert_eq!(functions[0].name, "zero");
+    assert!(functions[0].parameters.is_empty());
+    assert_eq!(functions[0].body, block(vec![]));
+
+    assert_eq!(functions[1].name, "add");
+    assert_eq!(parameter_names(functions[1]), vec!["a", "b"]);
+    assert_eq!(
+        functions[1].body,
+        block(vec![
+            statement(Statement::Return(
+                Some(binary(BinaryOperator::Add, variable("a"), variable("b"))),
+            )),
+        ])
+    );
+
+    assert_eq!(parameter_names(functions[2]), vec!["a"]);
+}
+
+#[test]
+fn program_function_spans() {
+    let items = parse_program("fn f(x) {\n  x;\n}");
+    let function = functions(&items)[0];
+
+    assert_eq!((function.span.start.index, function.span.end.index), (0, 16));
+    assert_eq!(function.span.end.line, 2);
+    assert_eq!((function.body.span.start.index, function.body.span.end.index), (8, 16));
+    assert_eq!(
+        (function.parameters[0].span.start.index, function.parameters[0].span.end.index),
+        (5, 6)
+    );
+}
+
+#[test]
+fn program_recovers_at_next_function() {
+    let (items, errors) = parse_program_with_errors("fn (a) {}\nfn g(a b) {}\nfn h() { 1 + }\nfn i() {}");
+
+    let names: Vec<_> = functions(&items).iter().map(|f| &f.name[..]).collect();
+    assert_eq!(names, vec!["h", "i"]);
+    assert_eq!(
+        errors,
+        vec![
+            "Expected identifier but found '('".to_string(),
+            "Expected one of: ')', ',' but found 'b'".to_string(),
+            expected_operand("'}'"),
+        ]
+    );
+}
+
+#[test]
+fn program_expects_items() {
+    let (items, errors) = parse_program_with_errors("1 + 2 fn f() {}");
+
+    assert_eq!(functions(&items).len(), 1);
+    assert_eq!(
+        errors,
+        vec!["Expected one of: end of input, 'fn' but found '1'".to_string()]
+    );
+}
+
+#[test]
+fn program_doc_comments() {
+    let module = parse_module(
+        "//! The module.\n//!\n//!Second paragraph.\n\n\
+         /// Adds two numbers.\n///\n/// Really.\nfn add(a, b) { a + b }\n\
+         // not documentation\nfn undocumented() {}\n\
+         ////not documentation either\n/// Last.\nfn last() {}",
+    );
+
+    assert_eq!(module.docs, vec![" The module.", "", "Second paragraph."]);
+
+    let functions = functions(&module.items);
+    assert_eq!(functions[0].docs, vec![" Adds two numbers.", "", " Really."]);
+    assert!(functions[1].docs.is_empty());
+    assert_eq!(functions[2].docs, vec![" Last."]);
+
+    // the span of a function does not include its doc comments
+    assert_eq!(functions[2].span.start.line, 12);
+}
+
+#[test]
+fn program_misplaced_doc_comments() {
+    let (module, errors) = parse_module_with_errors(
+        "fn f() {}\n//! Too late.\nfn g() {\n    /// Nothing to document.\n    1\n}\n/// At the end.",
+    );
+
+    assert!(module.docs.is_empty());
+    assert_eq!(functions(&module.items).len(), 2);
+    assert_eq!(functions(&module.items)[1].body, block_with_value(vec![], integer(1)));
+    assert_eq!(
+        errors,
+        vec![
+            "Inner doc comments are only allowed at the start of a file",
+            "Doc comments are only allowed in front of functions",
+            "Doc comments are only allowed in front of functions",
+        ]
+    );
+
+    let (_, errors) = parse_program_with_errors("/// Doc.\nlet a = 1; fn f() {}");
+    assert_eq!(
+        errors,
+        vec![
+            "Doc comments are only allowed in front of functions",
+            "Expected one of: end of input, 'fn' but found 'let'",
+        ]
+    );
+}
+
+#[test]
+fn program_doc_comments_in_function_bodies_are_warnings() {
+    let input = "fn f() {\n    /// Comment.\n    1\n}\n/// At the end.\n/// Really.";
+    let (tokens, _) = lexer::lex(input.as_bytes());
+    let (module, errors) = parser::program(&tokens);
+
+    assert_eq!(functions(&module.items)[0].body, block_with_value(vec![], integer(1)));
+    let severities: Vec<_> = errors.iter().map(|e| (e.severity(), e.start().line)).collect();
+    assert_eq!(severities, vec![(Severity::Warning, 1), (Severity::Error, 4)]);
+}
+
+#[test]
+fn program_doc_comments_after_missing_brace() {
+    let (items, errors) = parse_program_with_errors("fn f() { a;\n/// Doc of g.\nfn g() {}");
+
+    let functions = functions(&items);
+    assert_eq!(functions.len(), 2);
+    assert_eq!(functions[1].docs, vec![" Doc of g."]);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].ends_with("but found '/// Doc of g.'"), "{}", errors[0]);
+}
+
+#[test]
+fn program_recovers_at_documented_function() {
+    let (items, errors) = parse_program_with_errors("fn (a) {}\n/// Doc.\nfn g() {}");
+
+    let functions = functions(&items);
+    assert_eq!(functions.len(), 1);
+    assert_eq!(functions[0].docs, vec![" Doc."]);
+    assert_eq!(errors, vec!["Expected identifier but found '('"]);
+}
+
+#[test]
+fn program_missing_closing_brace() {
+    let (items, errors) = parse_program_with_errors("fn f() { a;\nfn g() {}");
+
+    let names: Vec<_> = functions(&items).iter().map(|f| &f.name[..]).collect();
+    assert_eq!(names, vec!["f", "g"]);
+    assert_eq!(
+        errors,
+        vec![
+            "Expected one of: identifier, integer, string, 'let', 'if', 'while', 'loop', \
+             'break', 'continue', 'return', '+', '++', '-', '--', '!', '~', '(', '{', '}', ';' \
+             but found 'fn'",
+        ]
+    );
+}
+
+#[test]
+fn statement_let() {
+    assert_eq!(
+        parse_statements("let x; let y = 1 + 2;"),
+        vec![
+            statement(Statement::Let("x".to_string(), None)),
+            statement(Statement::Let(
+                "y".to_string(),
+                Some(binary(BinaryOperator::Add, integer(1), integer(2))),
+            )),
+        ]
+    );
+}
+
+#[test]
+fn statement_expression() {
+    assert_eq!(
+        parse_statements("a = 1; f(a);"),
+        vec![
+            expression_statement(assign(None, variable("a"), integer(1))),
+            expression_statement(call(variable("f"), vec![variable("a")])),
+        ]
+    );
+}
+
+#[test]
+fn statement_empty() {
+    assert_eq!(
+        parse_statements(";; a;;"),
+        vec![expression_statement(variable("a"))]
+    );
+}
+
+#[test]
+fn statement_blocks() {
+    assert_eq!(
+        parse_statements("{ let a = 1; { a; } {} } x;"),
+        vec![
+            expression_statement(block_expression(block_with_value(
+                vec![
+                    statement(Statement::Let("a".to_string(), Some(integer(1)))),
+                    expression_statement(
+                        block_expression(block(vec![expression_statement(variable("a"))])),
+                    ),
+                ],
+                block_expression(block(vec![])),
+            ))),
+            expression_statement(variable("x")),
+        ]
+    );
+}
+
+#[test]
+fn statement_if_else() {
+    let a = || block(vec![expression_statement(variable("a"))]);
+    let b = || block(vec![expression_statement(variable("b"))]);
+
+    assert_eq!(
+        parse_statements("if x { a; } if x { a; } else { b; } if x { a; } else if y { b; } z;"),
+        vec![
+            expression_statement(if_expression(variable("x"), a(), None)),
+            expression_statement(if_expression(
+                variable("x"),
+                a(),
+                Some(block_expression(b())),
+            )),
+            expression_statement(if_expression(
+                variable("x"),
+                a(),
+                Some(if_expression(variable("y"), b(), None)),
+            )),
+            expression_statement(variable("z")),
+        ]
+    );
+}
+
+#[test]
+fn block_value() {
+    assert_eq!(
+        parse_block("{ let a = 1; a + 1 }"),
+        block_with_value(
+            vec![statement(Statement::Let("a".to_string(), Some(integer(1))))],
+            binary(BinaryOperator::Add, variable("a"), integer(1)),
+        )
+    );
+    assert_eq!(parse_block("{ f() }"), block_with_value(vec![], call(variable("f"), vec![])));
+}
+
+#[test]
+fn block_trailing_semicolon() {
+    assert_eq!(
+        parse_block("{ a; b; }"),
+        block(vec![
+            expression_statement(variable("a")),
+            expression_statement(variable("b")),
+        ])
+    );
+    assert_eq!(
+        parse_block("{ if c { 1 } else { 2 }; }"),
+        block(vec![
+            expression_statement(if_expression(
+                variable("c"),
+                block_with_value(vec![], integer(1)),
+                Some(block_expression(block_with_value(vec![], integer(2)))),
+            )),
+        ])
+    );
+}
+
+#[test]
+fn expr_if_yields_value() {
+    assert_eq!(
+        parse_statements("let x = if c { 1 } else { 2 };"),
+        vec![
+            statement(Statement::Let(
+                "x".to_string(),
+                Some(if_expression(
+                    variable("c"),
+                    block_with_value(vec![], integer(1)),
+                    Some(block_expression(block_with_value(vec![], integer(2)))),
+                )),
+            )),
+        ]
+    );
+    assert_eq!(
+        parse_expr("1 + { a; 2 } * if c { 3 } else { 4 }"),
+        binary(
+            BinaryOperator::Add,
+            integer(1),
+            binary(
+                BinaryOperator::Mul,
+                block_expression(block_with_value(
+                    vec![expression_statement(variable("a"))],
+                    integer(2),
+                )),
+                if_expression(
+                    variable("c"),
+                    block_with_value(vec![], integer(3)),
+                    Some(block_expression(block_with_value(vec![], integer(4)))),
+                ),
+            ),
+        )
+    );
+}
+
+#[test]
+fn expr_if_else_chain() {
+    assert_eq!(
+        parse_expr("if a { 1 } else if b { 2 } else { 3 }"),
+        if_expression(
+            variable("a"),
+            block_with_value(vec![], integer(1)),
+            Some(if_expression(
+                variable("b"),
+                block_with_value(vec![], integer(2)),
+                Some(block_expression(block_with_value(vec![], integer(3)))),
+            )),
+        )
+    );
+}
+
+#[test]
+fn dangling_else() {
+    // the braces decide which if an else belongs to
+    assert_eq!(
+        parse_expr("if a { if b { x } else { y } }"),
+        if_expression(
+            variable("a"),
+            block_with_value(
+                vec![],
+                if_expression(
+                    variable("b"),
+                    block_with_value(vec![], variable("x")),
+                    Some(block_expression(block_with_value(vec![], variable("y")))),
+                ),
+            ),
+            None,
+        )
+    );
+    assert_eq!(
+        parse_expr("if a { if b { x } } else { y }"),
+        if_expression(
+            variable("a"),
+            block_with_value(
+                vec![],
+                if_expression(variable("b"), block_with_value(vec![], variable("x")), None),
+            ),
+            Some(block_expression(block_with_value(vec![], variable("y")))),
+        )
+    );
+}
+
+#[test]
+fn block_like_statement_ends_expression() {
+    // in statement position `- 1` is not subtracted from the if
+    assert_eq!(
+        parse_block("{ if c { a } -1 }"),
+        block_with_value(
+            vec![
+                expression_statement(
+                    if_expression(variable("c"), block_with_value(vec![], variable("a")), None),
+                ),
+            ],
+            unary(UnaryOperator::Negate, integer(1)),
+        )
+    );
+    assert_eq!(
+        parse_block("{ { a } (b) }"),
+        block_with_value(
+            vec![
+                expression_statement(
+                    block_expression(block_with_value(vec![], variable("a"))),
+                ),
+            ],
+            variable("b"),
+        )
+    );
+    // elsewhere it is an operand like any other
+    assert_eq!(
+        parse_block("{ x = if c { a } - 1 }"),
+        block_with_value(
+            vec![],
+            assign(
+                None,
+                variable("x"),
+                binary(
+                    BinaryOperator::Sub,
+                    if_expression(variable("c"), block_with_value(vec![], variable("a")), None),
+                    integer(1),
+                ),
+            ),
+        )
+    );
+}
+
+#[test]
+fn recover_in_if_expression() {
+    let (statements, errors) = parse_statements_with_errors("if a { 1 } else 2; b;");
+
+    assert_eq!(statements, vec![expression_statement(variable("b"))]);
+    assert_eq!(errors, vec!["Expected one of: 'if', '{' but found '2'"]);
+
+    let (statements, errors) = parse_statements_with_errors("a b; c;");
+
+    assert_eq!(statements, vec![expression_statement(variable("c"))]);
+    assert_eq!(
+        errors,
+        vec![expected_continuation(&[TokenKind::Semicolon, TokenKind::RBrace], "'b'")]
+    );
+}
+
+#[test]
+fn statement_loops() {
+    assert_eq!(
+        parse_statements("while i < 10 { i += 1; continue; } loop { break; }"),
+        vec![
+            statement(Statement::While(
+                binary(BinaryOperator::Less, variable("i"), integer(10)),
+                block(vec![
+                    expression_statement(
+                        assign(Some(BinaryOperator::Add), variable("i"), integer(1)),
+                    ),
+                    statement(Statement::Continue),
+                ]),
+            )),
+            statement(Statement::Loop(block(vec![statement(Statement::Break)]))),
+        ]
+    );
+}
+
+#[test]
+fn statement_return() {
+    assert_eq!(
+        parse_statements("return; return a * 2;"),
+        vec![
+            statement(Statement::Return(None)),
+            statement(Statement::Return(
+                Some(binary(BinaryOperator::Mul, variable("a"), integer(2))),
+            )),
+        ]
+    );
+}
+
+#[test]
+fn statement_spans() {
+    let (tokens, _) = lexer::lex("{ let a = 1 ; if a {} else {} ; }".as_bytes());
+    let mut errors = Vec::new();
+    let (_, block) = parser::block(&tokens, &mut errors).expect("Parse error");
+
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    assert_eq!((block.span.start.index, block.span.end.index), (0, 33));
+
+    let spans: Vec<_> = block
+        .statements
+        .iter()
+        .map(|s| (s.span().start.index, s.span().end.index))
+        .collect();
+    assert_eq!(spans, vec![(2, 13), (14, 31)]);
+}
+
+#[test]
+fn statement_requires_semicolon() {
+    let (statements, errors) = parse_statements_with_errors("a = 1 b; c; break");
+
+    assert_eq!(statements, vec![expression_statement(variable("c"))]);
+    assert_eq!(
+        errors,
+        vec![
+            expected_continuation(&[TokenKind::Semicolon, TokenKind::RBrace], "'b'"),
+            "Expected ';' but found '}'".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn statement_recovery() {
+    let (statements, errors) =
+        parse_statements_with_errors("let = 1; a; if x y { b; } c; if x {} else d; e; let f + 1;");
+
+    assert_eq!(
+        statements,
+        vec![
+            expression_statement(variable("a")),
+            expression_statement(variable("c")),
+            expression_statement(variable("e")),
+        ]
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "Expected identifier but found '='".to_string(),
+            "Expected '{' but found 'y'".to_string(),
+            "Expected one of: 'if', '{' but found 'd'".to_string(),
+            "Expected one of: '=', ';' but found '+'".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn statement_recovery_inside_expression() {
+    let (statements, errors) = parse_statements_with_errors("let a = 1 + ; if * { b; } c;");
+
+    assert_eq!(
+        statements,
+        vec![
+            statement(Statement::Let(
+                "a".to_string(),
+                Some(binary(BinaryOperator::Add, integer(1), error())),
+            )),
+            expression_statement(if_expression(
+                error(),
+                block(vec![expression_statement(variable("b"))]),
+                None,
+            )),
+            expression_statement(variable("c")),
+        ]
+    );
+    assert_eq!(errors, vec![expected_operand("';'"), expected_operand("'*'")]);
+}
+
+fn expected_operand(found: &str) -> String {
+    format!(
+        "Expected one of: identifier, integer, string, 'if', '+', '++', '-', '--', '!', '~', \
+         '(', '{{' but found {}",
+        found
+    )
+}
+
+/// Tokens which may follow a complete expression.
+const CONTINUATIONS: &[TokenKind] = &[
+    TokenKind::Plus,
+    TokenKind::PlusPlus,
+    TokenKind::PlusEqual,
+    TokenKind::Minus,
+    TokenKind::MinusMinus,
+    TokenKind::MinusEqual,
+    TokenKind::Asterisk,
+    TokenKind::AsteriskEqual,
+    TokenKind::AsteriskAsterisk,
+    TokenKind::AsteriskAsteriskEqual,
+    TokenKind::Slash,
+    TokenKind::SlashEqual,
+    TokenKind::Percent,
+    TokenKind::PercentEqual,
+    TokenKind::Equal,
+    TokenKind::EqualEqual,
+    TokenKind::ExclamationEqual,
+    TokenKind::Less,
+    TokenKind::LessEqual,
+    TokenKind::Greater,
+    TokenKind::GreaterEqual,
+    TokenKind::AmpersandAmpersand,
+    TokenKind::PipePipe,
+    TokenKind::Ampersand,
+    TokenKind::AmpersandEqual,
+    TokenKind::Pipe,
+    TokenKind::PipeEqual,
+    TokenKind::Caret,
+    TokenKind::CaretEqual,
+    TokenKind::LessLess,
+    TokenKind::LessLessEqual,
+    TokenKind::GreaterGreater,
+    TokenKind::GreaterGreaterEqual,
+    TokenKind::LParen,
+    TokenKind::LBracket,
+    TokenKind::Dot,
+];
+
+/// The message for an unexpected token after a complete expression which
+/// could have been ended by one of the `terminators`.
+fn expected_continuation(terminators: &[TokenKind], found: &str) -> String {
+    let mut expected = CONTINUATIONS.to_vec();
+    expected.extend_from_slice(terminators);
+    expected.sort();
+
+    let names: Vec<_> = expected.iter().map(|k| k.to_string()).collect();
+    format!("Expected one of: {} but found {}", names.join(", "), found)
+}
+
+fn expected_end_of_input(found: &str) -> String {
+    expected_continuation(&[TokenKind::EndOfInput], found)
+}
+
+fn span_of(expr: &ExpressionNode) -> (usize, usize) {
+    (expr.span().start.index, expr.span().end.index)
+}
+
+fn parse_expr(input: &str) -> ExpressionNode {
+    let (tokens, errors) = lexer::lex(input.as_bytes());
+    assert!(errors.is_empty(), "Lex errors {:?}", errors);
+    let mut errors = Vec::new();
+    let (tokens, expr) = parser::expression(&tokens, &mut errors).expect("Parse error");
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    assert_eof(tokens);
+    expr
+}
+
+fn parse_with_errors(input: &str) -> (ExpressionNode, Vec<String>) {
+    let (tokens, mut errors) = lexer::lex(input.as_bytes());
+    let (expr, parse_errors) = parser::parse_expression(&tokens);
+    errors.extend(parse_errors);
+    let messages = errors.iter().map(|e| e.message().to_string()).collect();
+    (expr, messages)
+}
+
+fn parse_program(input: &str) -> Vec<Item> {
+    parse_module(input).items
+}
+
+fn parse_program_with_errors(input: &str) -> (Vec<Item>, Vec<String>) {
+    let (module, messages) = parse_module_with_errors(input);
+    (module.items, messages)
+}
+
+fn parse_module(input: &str) -> Module {
+    let (module, errors) = parse_module_with_errors(input);
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    module
+}
+
+fn parse_module_with_errors(input: &str) -> (Module, Vec<String>) {
+    let (tokens, mut errors) = lexer::lex(input.as_bytes());
+    let (module, parse_errors) = parser::program(&tokens);
+    errors.extend(parse_errors);
+    let messages = errors.iter().map(|e| e.message().to_string()).collect();
+    (module, messages)
+}
+
+fn parse_block(input: &str) -> Block {
+    let (tokens, errors) = lexer::lex(input.as_bytes());
+    assert!(errors.is_empty(), "Lex errors {:?}", errors);
+    let mut errors = Vec::new();
+    let (tokens, block) = parser::block(&tokens, &mut errors).expect("Parse error");
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    assert_eof(tokens);
+    block
+}
+
+fn parse_statements(input: &str) -> Vec<StatementNode> {
+    let (statements, errors) = parse_statements_with_errors(input);
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    statements
+}
+
+/// Parses `input` as the statements of a block.
+fn parse_statements_with_errors(input: &str) -> (Vec<StatementNode>, Vec<String>) {
+    let input = format!("{{{}}}", input);
+    let (tokens, mut errors) = lexer::lex(input.as_bytes());
+    let mut parse_errors = Vec::new();
+    let (tokens, block) = parser::block(&tokens, &mut parse_errors).expect("Parse error");
+    assert_eof(tokens);
+    errors.extend(parse_errors);
+    let messages = errors.iter().map(|e| e.message().to_string()).collect();
+    (block.statements, messages)
+}
+
+fn functions(items: &[Item]) -> Vec<&FunctionDecl> {
+    items
+        .iter()
+        .map(|item| match *item {
+            Item::Function(ref function) => function,
+        })
+        .collect()
+}
+
+fn parameter_names(function: &FunctionDecl) -> Vec<&str> {
+    function.parameters.iter().map(|p| &p.name[..]).collect()
+}
+
+fn assert_eof(tokens: &[Token]) {
+    assert!(
+        tokens.len() == 1 && tokens[0].kind == TokenKind::EndOfInput,
+        "Expected end of file got tokens {:?}",
+        tokens.iter().map(|t| &t.text).collect::<Vec<&String>>()
+    );
+}
+
+fn node(kind: ExpressionKind) -> ExpressionNode {
+    ExpressionNode::new(kind, Span::default())
+}
+
+fn integer(value: i32) -> ExpressionNode {
+    node(ExpressionKind::Integer(value))
+}
+
+fn string(value: &str) -> ExpressionNode {
+    node(ExpressionKind::String(value.to_string()))
+}
+
+fn interpolated(parts: Vec<StringPart>) -> ExpressionNode {
+    node(ExpressionKind::Interpolated(parts))
+}
+
+fn text(value: &str) -> StringPart {
+    StringPart::Text(value.to_string())
+}
+
+fn part(expr: ExpressionNode) -> StringPart {
+    StringPart::Expression(expr)
+}
+
+fn variable(name: &str) -> ExpressionNode {
+    node(ExpressionKind::Variable(name.to_string()))
+}
+
+fn error() -> ExpressionNode {
+    node(ExpressionKind::Error)
+}
+
+fn binary(operator: BinaryOperator, lhs: ExpressionNode, rhs: ExpressionNode) -> ExpressionNode {
+    node(ExpressionKind::Binary(operator, Box::new(lhs), Box::new(rhs)))
+}
+
+fn assign(
+    operator: Option<BinaryOperator>,
+    target: ExpressionNode,
+    value: ExpressionNode,
+) -> ExpressionNode {
+    node(ExpressionKind::Assign(operator, Box::new(target), Box::new(value)))
+}
+
+fn unary(operator: UnaryOperator, operand: ExpressionNode) -> ExpressionNode {
+    node(ExpressionKind::Unary(operator, Box::new(operand)))
+}
+
+fn call(callee: ExpressionNode, arguments: Vec<ExpressionNode>) -> ExpressionNode {
+    node(ExpressionKind::Call(Box::new(callee), arguments))
+}
+
+fn index(target: ExpressionNode, index: ExpressionNode) -> ExpressionNode {
+    node(ExpressionKind::Index(Box::new(target), Box::new(index)))
+}
+
+fn field(target: ExpressionNode, name: &str) -> ExpressionNode {
+    node(ExpressionKind::Field(Box::new(target), name.to_string()))
+}
+
+fn statement(kind: Statement) -> StatementNode {
+    StatementNode::new(kind, Span::default())
+}
+
+fn expression_statement(expr: ExpressionNode) -> StatementNode {
+    statement(Statement::Expression(expr))
+}
+
+fn block(statements: Vec<StatementNode>) -> Block {
+    Block {
+        statements,
+        value: None,
+        span: Span::default(),
+    }
+}
+
+fn block_with_value(statements: Vec<StatementNode>, value: ExpressionNode) -> Block {
+    Block {
+        statements,
+        value: Some(Box::new(value)),
+        span: Span::default(),
+    }
+}
+
+fn block_expression(block: Block) -> ExpressionNode {
+    node(ExpressionKind::Block(block))
+}
+
+fn if_expression(
+    condition: ExpressionNode,
+    then_block: Block,
+    else_branch: Option<ExpressionNode>,
+) -> ExpressionNode {
+    node(ExpressionKind::If(
+        Box::new(condition),
+        then_block,
+        else_branch.map(Box::new),
+    ))
+}
@@ -0,0 +1,115 @@
+use simplelang::*;
+use simplelang::diagnostics::*;
+
+#[test]
+fn increment_variable() {
+    assert!(check("++a").is_empty());
+    assert!(check("a-- + --b").is_empty());
+}
+
+#[test]
+fn increment_field_and_index() {
+    assert!(check("p.x++ + --xs[0]").is_empty());
+    assert!(check("f(a++)").is_empty());
+}
+
+#[test]
+fn increment_call() {
+    let diagnostics = check("f(a)++");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!((diagnostics[0].start.index, diagnostics[0].end.index), (0, 4));
+}
+
+#[test]
+fn increment_literal() {
+    let diagnostics = check("1 + 5++");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Operand of '++' must be a variable, field or index");
+    assert_eq!(
+        diagnostics[0].label,
+        Some("this expression cannot be incremented".to_string())
+    );
+    assert_eq!((diagnostics[0].start.index, diagnostics[0].end.index), (4, 5));
+}
+
+#[test]
+fn decrement_string() {
+    let diagnostics = check("--\"text\"");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Operand of '--' must be a variable, field or index");
+    assert_eq!((diagnostics[0].start.index, diagnostics[0].end.index), (2, 8));
+}
+
+#[test]
+fn chained_postfix_operators() {
+    let messages: Vec<_> = check("5--++").into_iter().map(|d| d.message).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Operand of '++' must be a variable, field or index",
+            "Operand of '--' must be a variable, field or index",
+        ]
+    );
+}
+
+#[test]
+fn nested_in_expressions() {
+    assert_eq!(check("a = (1 + 2)++ * 3").len(), 1);
+    assert_eq!(check("-(++(a))").len(), 0);
+}
+
+#[test]
+fn error_nodes_are_ignored() {
+    let (tokens, _) = lexer::lex("++$".as_bytes());
+    let (expr, _) = parser::parse_expression(&tokens);
+    assert!(semantic::check_expression(&expr).is_empty());
+}
+
+#[test]
+fn break_and_continue_inside_loops() {
+    let diagnostics = check_program(
+        "fn f() { loop { break; } while a { if b { continue; } { break; } } }",
+    );
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn break_and_continue_outside_loops() {
+    let diagnostics = check_program("fn f() { break; loop {} if a { continue; } }");
+
+    let messages: Vec<_> = diagnostics.iter().map(|d| &d.message[..]).collect();
+    assert_eq!(
+        messages,
+        vec!["'break' outside of a loop", "'continue' outside of a loop"]
+    );
+    assert_eq!(
+        diagnostics[0].label,
+        Some("cannot 'break' outside of a loop".to_string())
+    );
+    assert_eq!((diagnostics[0].start.index, diagnostics[0].end.index), (9, 15));
+}
+
+#[test]
+fn statements_are_checked() {
+    let diagnostics = check_program("fn f() { let a = 1++; return --2; }");
+    assert_eq!(diagnostics.len(), 2);
+}
+
+fn check_program(input: &str) -> Vec<Diagnostic> {
+    let (tokens, errors) = lexer::lex(input.as_bytes());
+    assert!(errors.is_empty(), "Lex errors {:?}", errors);
+    let (module, errors) = parser::program(&tokens);
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    semantic::check_program(&module.items)
+}
+
+fn check(input: &str) -> Vec<Diagnostic> {
+    let (tokens, errors) = lexer::lex(input.as_bytes());
+    assert!(errors.is_empty(), "Lex errors {:?}", errors);
+    let (expr, errors) = parser::parse_expression(&tokens);
+    assert!(errors.is_empty(), "Parse errors {:?}", errors);
+    semantic::check_expression(&expr)
+}
@@ -0,0 +1,76 @@
+use simplelang::*;
+use simplelang::lexer;
+use simplelang::source_map::*;
+
+#[test]
+fn add_files() {
+    let mut source_map = SourceMap::new();
+    let a = source_map.add_file("a.sl".to_string(), "1".to_string());
+    let b = source_map.add_file("b.sl".to_string(), "2".to_string());
+
+    assert_ne!(a, b);
+    assert_eq!(source_map.file(a).name(), "a.sl");
+    assert_eq!(source_map.file(b).text(), "2");
+    assert_eq!(source_map.files().len(), 2);
+}
+
+#[test]
+fn unregistered_file_id() {
+    let mut source_map = SourceMap::new();
+    let id = source_map.add_file("a.sl".to_string(), "1".to_string());
+
+    assert_ne!(id, FileId::default());
+    let (tokens, _) = lexer::lex("1".as_bytes());
+    assert_eq!(tokens[0].start.file, FileId::default());
+}
+
+#[test]
+fn line_text() {
+    let mut source_map = SourceMap::new();
+    let id = source_map.add_file("a.sl".to_string(), "abc\r\n\ndef".to_string());
+    let file = source_map.file(id);
+
+    assert_eq!(file.line_text(0), Some("abc"));
+    assert_eq!(file.line_text(1), Some(""));
+    assert_eq!(file.line_text(2), Some("def"));
+    assert_eq!(file.line_text(3), None);
+}
+
+#[test]
+fn resolve_token() {
+    let mut source_map = SourceMap::new();
+    source_map.add_file("a.sl".to_string(), "1 + 2".to_string());
+    let id = source_map.add_file("b.sl".to_string(), "1\n  \"\u{e4}\" + x".to_string());
+
+    let (tokens, errors) = lexer::lex_file(source_map.file(id));
+    assert!(errors.is_empty());
+    let location = source_map.resolve(tokens[3].start);
+
+    assert_eq!(
+        location,
+        SourceLocation {
+            file_name: "b.sl",
+            line: 2,
+            column: 9,
+            line_text: "  \"\u{e4}\" + x",
+        }
+    );
+}
+
+#[test]
+fn resolve_end_of_file() {
+    let mut source_map = SourceMap::new();
+    let id = source_map.add_file("a.sl".to_string(), "1\n".to_string());
+
+    let position = TextPosition {
+        file: id,
+        index: 2,
+        line: 1,
+        column: 0,
+    };
+    let location = source_map.resolve(position);
+
+    assert_eq!(location.line, 2);
+    assert_eq!(location.column, 1);
+    assert_eq!(location.line_text, "");
+}